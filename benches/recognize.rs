@@ -6,12 +6,13 @@ extern crate test;
 #[path = "../tests/helpers/mod.rs"]
 mod helpers;
 
+use async_std::io::BufReader;
 use async_std::task::block_on;
-use elaine::{recognize, Head};
+use elaine::{recognize, recognize_buffered, Error, Head};
 use helpers::AsyncBuffer;
 use test::Bencher;
 
-async fn run(mut buffer: AsyncBuffer) -> Result<Head, std::io::Error> {
+async fn run(mut buffer: AsyncBuffer) -> Result<Head, Error> {
   recognize(&mut buffer).await
 }
 
@@ -24,3 +25,15 @@ fn recognize_content(bencher: &mut Bencher) {
     assert_eq!(result.unwrap().len(), Some(3));
   })
 }
+
+#[bench]
+fn recognize_buffered_content(bencher: &mut Bencher) {
+  bencher.iter(|| {
+    let mut buff = BufReader::new(AsyncBuffer::new(
+      "GET /hello-world HTTP/1.1\r\nContent-Length: 3\r\n\r\n",
+    ));
+    let result = block_on(async { recognize_buffered(&mut buff).await });
+    assert!(result.is_ok());
+    assert_eq!(result.unwrap().len(), Some(3));
+  })
+}
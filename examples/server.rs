@@ -0,0 +1,25 @@
+use async_std::prelude::*;
+use async_std::task::block_on;
+use elaine::server::{Request, Response, Server};
+use std::error::Error;
+
+async fn route(mut request: Request) -> Response {
+  let mut body = Vec::new();
+
+  match request.body().read_to_end(&mut body).await {
+    Ok(0) => Response::new(200).unwrap(),
+    Ok(_) => Response::new(200).unwrap().body(body),
+    Err(e) => Response::new(400).unwrap().body(e.to_string()),
+  }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+  let addr = std::env::var("ELAINE_ADDR").unwrap_or(String::from("0.0.0.0:8080"));
+  println!("[debug] starting server on '{}'", addr);
+
+  block_on(async {
+    let server = Server::bind(addr.as_str())?;
+    server.serve(route).await;
+    Ok(())
+  })
+}
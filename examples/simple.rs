@@ -2,41 +2,46 @@ use async_std::io::{Read, Write};
 use async_std::net::{TcpListener, TcpStream};
 use async_std::prelude::*;
 use async_std::task::{block_on, spawn};
-use elaine::{recognize, Head};
+use elaine::{Connection, Head};
 use std::error::Error;
 
-async fn route<R>(conn: Head, stream: &mut R) -> Result<Option<Vec<u8>>, std::io::Error>
+async fn route<B, W>(_conn: Head, reader: &mut B, stream: &mut W) -> Result<Option<Vec<u8>>, std::io::Error>
 where
-  R: Read + Write + std::marker::Unpin,
+  B: Read + std::marker::Unpin,
+  W: Write + std::marker::Unpin,
 {
-  if let Some(len) = conn.len() {
-    let buffered = stream.bytes();
-    let mut taker = buffered.take(len);
-    let mut body = Vec::with_capacity(len);
-
-    while let Some(Ok(byte)) = taker.next().await {
-      body.push(byte);
-    }
+  let mut body = Vec::new();
+  reader.read_to_end(&mut body).await?;
 
+  if !body.is_empty() {
     let res = format!(
       "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}\r\n",
-      len + 2,
-      String::from_utf8(body).unwrap()
+      body.len() + 2,
+      String::from_utf8_lossy(&body)
     );
-    stream.write(res.as_bytes()).await?;
+    stream.write_all(res.as_bytes()).await?;
     return Ok(None);
   }
 
-  stream.write(b"HTTP/1.1 200 OK\r\n\r\n").await?;
+  stream
+    .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+    .await?;
   Ok(None)
 }
 
-async fn handle(mut stream: TcpStream) -> Result<(), std::io::Error> {
-  let head = recognize(&mut stream).await?;
-  if let Err(e) = route(head, &mut stream).await {
-    println!("unable to route: {:?}", e);
+async fn handle(stream: TcpStream) -> Result<(), std::io::Error> {
+  let mut writer = &stream;
+  let mut connection = Connection::new(&stream);
+
+  while let Some(result) = connection.next().await {
+    let (head, body) = result?;
+
+    if let Err(e) = route(head, body, &mut writer).await {
+      println!("unable to route: {:?}", e);
+      break;
+    }
   }
-  drop(stream);
+
   Ok(())
 }
 
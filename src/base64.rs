@@ -0,0 +1,111 @@
+//! The standard base64 alphabet of RFC 4648, as used by `Basic` credentials and
+//! the WebSocket handshake.
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn value(symbol: u8) -> Option<u32> {
+  match symbol {
+    b'A'..=b'Z' => Some(u32::from(symbol - b'A')),
+    b'a'..=b'z' => Some(u32::from(symbol - b'a') + 26),
+    b'0'..=b'9' => Some(u32::from(symbol - b'0') + 52),
+    b'+' => Some(62),
+    b'/' => Some(63),
+    _ => None,
+  }
+}
+
+pub(crate) fn encode(input: &[u8]) -> String {
+  let mut output = String::with_capacity(input.len().div_ceil(3) * 4);
+
+  for group in input.chunks(3) {
+    let bits = group
+      .iter()
+      .enumerate()
+      .fold(0u32, |bits, (i, b)| bits | u32::from(*b) << (16 - i * 8));
+
+    for i in 0..4 {
+      if i <= group.len() {
+        output.push(char::from(ALPHABET[(bits >> (18 - i * 6)) as usize & 63]));
+      } else {
+        output.push('=');
+      }
+    }
+  }
+
+  output
+}
+
+/// Decodes padded (or unpadded) input, returning `None` for anything outside of the alphabet, with
+/// a length that cannot be produced by `encode`, or with non-zero bits after the last byte, so that
+/// every byte string has exactly one accepted encoding.
+#[allow(clippy::manual_is_multiple_of)]
+pub(crate) fn decode(input: &str) -> Option<Vec<u8>> {
+  let trimmed = input.trim_end_matches('=');
+
+  if input.len() - trimmed.len() > 2 || (input.len() != trimmed.len() && input.len() % 4 != 0) {
+    return None;
+  }
+
+  let mut output = Vec::with_capacity(trimmed.len() / 4 * 3 + 2);
+
+  for group in trimmed.as_bytes().chunks(4) {
+    if group.len() == 1 {
+      return None;
+    }
+
+    let mut bits = 0u32;
+    for (i, symbol) in group.iter().enumerate() {
+      bits |= value(*symbol)? << (18 - i * 6);
+    }
+
+    if bits & ((1 << (32 - group.len() * 8)) - 1) != 0 {
+      return None;
+    }
+
+    for i in 0..group.len() - 1 {
+      output.push((bits >> (16 - i * 8)) as u8);
+    }
+  }
+
+  Some(output)
+}
+
+#[cfg(test)]
+mod test {
+  use super::{decode, encode};
+
+  #[test]
+  fn test_round_trip() {
+    let cases: &[(&[u8], &str)] = &[
+      (b"", ""),
+      (b"f", "Zg=="),
+      (b"fo", "Zm8="),
+      (b"foo", "Zm9v"),
+      (b"foob", "Zm9vYg=="),
+      (b"Aladdin:open sesame", "QWxhZGRpbjpvcGVuIHNlc2FtZQ=="),
+    ];
+
+    for (plain, encoded) in cases {
+      assert_eq!(encode(plain), *encoded);
+      assert_eq!(decode(encoded).as_deref(), Some(*plain));
+    }
+  }
+
+  #[test]
+  fn test_invalid() {
+    assert_eq!(decode("Zm9v!"), None);
+    assert_eq!(decode("Z"), None);
+    assert_eq!(decode("Zm9vY==="), None);
+    assert_eq!(decode("Zg="), None);
+    assert_eq!(decode("Zg"), Some(b"f".to_vec()));
+  }
+
+  #[test]
+  fn test_non_canonical() {
+    assert_eq!(decode("QQ=="), Some(b"A".to_vec()));
+    assert_eq!(decode("QR=="), None);
+    assert_eq!(decode("QR"), None);
+    assert_eq!(decode("Zm9="), None);
+    assert_eq!(decode("Zm8="), Some(b"fo".to_vec()));
+  }
+}
@@ -0,0 +1,133 @@
+//! Blocking counterparts of the parsing functions exported at the crate root, for readers that
+//! implement [`std::io::Read`][read] (or [`std::io::BufRead`][buf-read]) and callers that are not
+//! running inside of an async executor. They share their implementation with the async functions
+//! and provide the same guarantee of never reading past the end of the head.
+//!
+//! ```rust
+//! use std::io::Read;
+//! use elaine::{blocking, RequestMethod};
+//!
+//! let mut req: &[u8] = b"GET /elaine HTTP/1.1\r\nContent-Length: 3\r\n\r\nhey";
+//! let head = blocking::recognize(&mut req).unwrap();
+//! assert_eq!(head.method(), Some(RequestMethod::GET));
+//! let mut body = String::new();
+//! req.read_to_string(&mut body).unwrap();
+//! assert_eq!(body, "hey");
+//! ```
+//!
+//! [read]: https://doc.rust-lang.org/std/io/trait.Read.html
+//! [buf-read]: https://doc.rust-lang.org/std/io/trait.BufRead.html
+use std::io::{BufRead, ErrorKind, Read};
+
+use crate::error::Error;
+use crate::head::{Builder, Head};
+use crate::machine::{exhausted, insert_lines, Budget, Feeder, Insert, Terminator};
+use crate::options::RecognizeOptions;
+use crate::response::{ResponseBuilder, ResponseHead};
+
+fn read_some<R>(reader: &mut R, buf: &mut [u8]) -> Result<usize, Error>
+where
+  R: Read,
+{
+  loop {
+    match reader.read(buf) {
+      Err(ref e) if e.kind() == ErrorKind::Interrupted => continue,
+      result => return Ok(result?),
+    }
+  }
+}
+
+fn read_lines<R, B>(mut reader: R, builder: B, options: &RecognizeOptions) -> Result<B, Error>
+where
+  R: Read,
+  B: Insert,
+{
+  let mut feeder = Feeder::new(builder, options);
+
+  loop {
+    let mut buf = vec![0; feeder.wanted()?];
+
+    match read_some(&mut reader, &mut buf)? {
+      0 => return feeder.eof(),
+      size => {
+        if let Some((builder, _)) = feeder.feed(&buf[..size])? {
+          return Ok(builder);
+        }
+      }
+    }
+  }
+}
+
+/// The blocking version of [`recognize`](../fn.recognize.html).
+pub fn recognize<R>(reader: R) -> Result<Head, Error>
+where
+  R: Read,
+{
+  recognize_with(reader, &RecognizeOptions::default())
+}
+
+/// The blocking version of [`recognize_with`](../fn.recognize_with.html).
+pub fn recognize_with<R>(reader: R, options: &RecognizeOptions) -> Result<Head, Error>
+where
+  R: Read,
+{
+  let builder = read_lines(reader, Builder::with_options(options), options)?;
+  Ok(builder.collect::<Head>())
+}
+
+/// The blocking version of [`recognize_response`](../fn.recognize_response.html).
+pub fn recognize_response<R>(reader: R) -> Result<ResponseHead, Error>
+where
+  R: Read,
+{
+  recognize_response_with(reader, &RecognizeOptions::response())
+}
+
+/// The blocking version of [`recognize_response_with`](../fn.recognize_response_with.html).
+pub fn recognize_response_with<R>(reader: R, options: &RecognizeOptions) -> Result<ResponseHead, Error>
+where
+  R: Read,
+{
+  let builder = read_lines(reader, ResponseBuilder::with_options(options), options)?;
+  Ok(builder.collect::<ResponseHead>())
+}
+
+/// The blocking version of [`recognize_buffered`](../fn.recognize_buffered.html).
+pub fn recognize_buffered<R>(reader: R) -> Result<Head, Error>
+where
+  R: BufRead,
+{
+  recognize_buffered_with(reader, &RecognizeOptions::default())
+}
+
+/// The blocking version of [`recognize_buffered_with`](../fn.recognize_buffered_with.html).
+pub fn recognize_buffered_with<R>(mut reader: R, options: &RecognizeOptions) -> Result<Head, Error>
+where
+  R: BufRead,
+{
+  let mut budget = Budget::new(&options.limits);
+  let mut terminator = Terminator::new(options);
+  let mut head = Vec::new();
+
+  loop {
+    let available = match reader.fill_buf() {
+      Err(ref e) if e.kind() == ErrorKind::Interrupted => continue,
+      result => result?,
+    };
+    let (amount, done) = terminator.scan(available);
+    head.extend_from_slice(&available[0..amount]);
+    reader.consume(amount);
+    budget.read(amount)?;
+
+    if done {
+      break;
+    }
+
+    if amount == 0 {
+      return Err(exhausted(&head));
+    }
+  }
+
+  let builder = insert_lines(&head, Builder::with_options(options), &mut budget)?;
+  Ok(builder.collect::<Head>())
+}
@@ -0,0 +1,290 @@
+use futures_io::AsyncRead;
+use std::io::ErrorKind;
+use std::marker::Unpin;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use crate::error::Error;
+use crate::framing::BodyFraming;
+use crate::head::{is_field, Head, OWS};
+use crate::response::ResponseHead;
+
+// The longest chunk-size line (including extensions) or trailer line that will be buffered.
+const MAX_LINE: usize = 4096;
+
+// The most trailer fields that will be kept after the last chunk, whether or not a maximum size is
+// set.
+const MAX_TRAILERS: usize = 64;
+
+#[derive(Debug, Clone, Copy)]
+enum State {
+  Length(usize),
+  Size,
+  Data(usize),
+  DataEnd,
+  Trailer,
+  Close,
+  Done,
+}
+
+fn parse_chunk_size(line: &[u8]) -> Result<usize, Error> {
+  let line = std::str::from_utf8(line).map_err(|_| Error::InvalidChunk)?;
+  // Chunk extensions are permitted after the size but carry no meaning for this reader.
+  let size = line.split(';').next().unwrap_or("").trim();
+
+  if size.is_empty() || !size.bytes().all(|b| b.is_ascii_hexdigit()) {
+    return Err(Error::InvalidChunk);
+  }
+
+  usize::from_str_radix(size, 16).map_err(|_| Error::InvalidChunk)
+}
+
+// Trailer fields follow the same rules as header fields; a token for a name and no control
+// characters other than HTAB.
+fn parse_trailer(line: &[u8]) -> Result<(String, String), Error> {
+  let line = std::str::from_utf8(line).map_err(|_| Error::InvalidChunk)?;
+
+  match line.split_once(':') {
+    Some((key, value)) if is_field(key, value) => Ok((key.to_string(), value.trim_matches(OWS).to_string())),
+    _ => Err(Error::InvalidChunk),
+  }
+}
+
+fn exhausted() -> std::io::Error {
+  std::io::Error::new(ErrorKind::UnexpectedEof, "Reader exhausted before end of message body")
+}
+
+/// A reader over the body of a message whose head has already been consumed by [`recognize`],
+/// framed either by a `Content-Length` or by the [chunked transfer coding][chunked] (or, for
+/// responses, by the connection closing). The body will never read past the end of the message,
+/// leaving the underlying reader positioned at the start of the next message.
+///
+/// ```rust
+/// use async_std::prelude::*;
+/// use async_std::task::block_on;
+/// use elaine::{recognize, Body};
+///
+/// block_on(async {
+///   let mut req: &[u8] = b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nhey\r\n0\r\n\r\n";
+///   let head = recognize(&mut req).await.unwrap();
+///   let mut body = Body::new(&head, &mut req).unwrap();
+///   let mut content = String::new();
+///   body.read_to_string(&mut content).await.unwrap();
+///   assert_eq!(content, "hey");
+/// });
+/// ```
+///
+/// [`recognize`]: fn.recognize.html
+/// [chunked]: https://tools.ietf.org/html/rfc7230#section-4.1
+#[derive(Debug)]
+pub struct Body<R> {
+  reader: R,
+  state: State,
+  line: Vec<u8>,
+  read: usize,
+  max: Option<usize>,
+  trailers: Vec<(String, String)>,
+}
+
+impl<R> Body<R>
+where
+  R: AsyncRead + Unpin,
+{
+  /// Picks the framing of the body based on the [`framing`] of the head; a head with neither a
+  /// `Transfer-Encoding` nor a `Content-Length` has an empty body.
+  ///
+  /// [`framing`]: struct.Head.html#method.framing
+  pub fn new(head: &Head, reader: R) -> Result<Self, Error> {
+    match head.framing() {
+      BodyFraming::Chunked => Ok(Body::chunked(reader)),
+      BodyFraming::Length(len) => Ok(Body::sized(reader, len)),
+      BodyFraming::None => Ok(Body::sized(reader, 0)),
+      BodyFraming::CloseDelimited => Err(Error::InvalidTransferEncoding),
+    }
+  }
+
+  /// Picks the framing of a response body based on the [`framing`] of its head. A body delimited by
+  /// the connection closing is read until the reader is exhausted. Responses to `HEAD` requests
+  /// have no body regardless of their head, and should use [`sized`] with a length of zero.
+  ///
+  /// [`framing`]: struct.ResponseHead.html#method.framing
+  /// [`sized`]: #method.sized
+  pub fn response(head: &ResponseHead, reader: R) -> Self {
+    match head.framing() {
+      BodyFraming::Chunked => Body::chunked(reader),
+      BodyFraming::Length(len) => Body::sized(reader, len),
+      BodyFraming::None => Body::sized(reader, 0),
+      BodyFraming::CloseDelimited => Body::close_delimited(reader),
+    }
+  }
+
+  pub fn sized(reader: R, len: usize) -> Self {
+    let state = if len == 0 { State::Done } else { State::Length(len) };
+    Body {
+      reader,
+      state,
+      line: Vec::new(),
+      read: 0,
+      max: None,
+      trailers: Vec::new(),
+    }
+  }
+
+  pub fn chunked(reader: R) -> Self {
+    Body {
+      reader,
+      state: State::Size,
+      line: Vec::new(),
+      read: 0,
+      max: None,
+      trailers: Vec::new(),
+    }
+  }
+
+  /// A body that ends when the reader is exhausted; only valid for responses.
+  pub fn close_delimited(reader: R) -> Self {
+    Body {
+      reader,
+      state: State::Close,
+      line: Vec::new(),
+      read: 0,
+      max: None,
+      trailers: Vec::new(),
+    }
+  }
+
+  /// Limits the number of body bytes that will be read, including the trailer lines of chunked
+  /// bodies; reading past it results in an error.
+  pub fn max_size(self, max: usize) -> Self {
+    Body { max: Some(max), ..self }
+  }
+
+  /// Returns true once the entire body (including any trailers) has been read.
+  pub fn is_done(&self) -> bool {
+    matches!(self.state, State::Done)
+  }
+
+  /// The trailer fields sent after the last chunk, available once the body has been read.
+  pub fn trailers(&self) -> &[(String, String)] {
+    &self.trailers
+  }
+
+  pub fn into_inner(self) -> R {
+    self.reader
+  }
+
+  fn check_size(&self, upcoming: usize) -> Result<(), Error> {
+    match self.max {
+      Some(max) if self.read.saturating_add(upcoming) > max => Err(Error::BodyTooLarge),
+      _ => Ok(()),
+    }
+  }
+
+  // Reads single bytes until a LF, returning the accumulated line without its CR LF. Partial lines
+  // are kept on the body between polls.
+  fn poll_line(&mut self, cx: &mut Context) -> Poll<Result<Vec<u8>, std::io::Error>> {
+    loop {
+      let mut byte = [0u8];
+      match Pin::new(&mut self.reader).poll_read(cx, &mut byte) {
+        Poll::Pending => return Poll::Pending,
+        Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+        Poll::Ready(Ok(0)) => return Poll::Ready(Err(exhausted())),
+        Poll::Ready(Ok(_)) if byte[0] == b'\n' => {
+          let mut line = std::mem::take(&mut self.line);
+          if line.last() == Some(&b'\r') {
+            line.pop();
+          }
+          return Poll::Ready(Ok(line));
+        }
+        Poll::Ready(Ok(_)) if self.line.len() >= MAX_LINE => return Poll::Ready(Err(Error::InvalidChunk.into())),
+        Poll::Ready(Ok(_)) => self.line.push(byte[0]),
+      }
+    }
+  }
+
+  // Reads chunk or content bytes directly into the destination, never more than `remaining`.
+  fn poll_data(&mut self, cx: &mut Context, dest: &mut [u8], remaining: usize) -> Poll<Result<usize, std::io::Error>> {
+    let amount = std::cmp::min(remaining, dest.len());
+    match Pin::new(&mut self.reader).poll_read(cx, &mut dest[0..amount]) {
+      Poll::Ready(Ok(0)) if amount > 0 => Poll::Ready(Err(exhausted())),
+      Poll::Ready(Ok(size)) => {
+        self.read += size;
+        Poll::Ready(Ok(size))
+      }
+      other => other,
+    }
+  }
+
+  fn poll_body(&mut self, cx: &mut Context, dest: &mut [u8]) -> Poll<Result<usize, std::io::Error>> {
+    loop {
+      match self.state {
+        State::Done => return Poll::Ready(Ok(0)),
+        State::Length(remaining) => {
+          self.check_size(remaining)?;
+          let size = ready!(self.poll_data(cx, dest, remaining))?;
+          self.state = if remaining > size {
+            State::Length(remaining - size)
+          } else {
+            State::Done
+          };
+          return Poll::Ready(Ok(size));
+        }
+        State::Close if dest.is_empty() => return Poll::Ready(Ok(0)),
+        State::Close => {
+          let size = ready!(Pin::new(&mut self.reader).poll_read(cx, dest))?;
+          self.check_size(size)?;
+          self.read += size;
+          if size == 0 {
+            self.state = State::Done;
+          }
+          return Poll::Ready(Ok(size));
+        }
+        State::Data(remaining) => {
+          let size = ready!(self.poll_data(cx, dest, remaining))?;
+          self.state = if remaining > size {
+            State::Data(remaining - size)
+          } else {
+            State::DataEnd
+          };
+          return Poll::Ready(Ok(size));
+        }
+        State::Size => {
+          let line = ready!(self.poll_line(cx))?;
+          let size = parse_chunk_size(&line)?;
+          self.check_size(size)?;
+          self.state = if size == 0 { State::Trailer } else { State::Data(size) };
+        }
+        State::DataEnd => {
+          let line = ready!(self.poll_line(cx))?;
+          if !line.is_empty() {
+            return Poll::Ready(Err(Error::InvalidChunk.into()));
+          }
+          self.state = State::Size;
+        }
+        State::Trailer => {
+          let line = ready!(self.poll_line(cx))?;
+          if line.is_empty() {
+            self.state = State::Done;
+            return Poll::Ready(Ok(0));
+          }
+          // Trailer lines count toward the maximum size like the body they follow.
+          self.check_size(line.len())?;
+          if self.trailers.len() >= MAX_TRAILERS {
+            return Poll::Ready(Err(Error::InvalidChunk.into()));
+          }
+          self.read += line.len();
+          self.trailers.push(parse_trailer(&line)?);
+        }
+      }
+    }
+  }
+}
+
+impl<R> AsyncRead for Body<R>
+where
+  R: AsyncRead + Unpin,
+{
+  fn poll_read(self: Pin<&mut Self>, cx: &mut Context, dest: &mut [u8]) -> Poll<Result<usize, std::io::Error>> {
+    self.get_mut().poll_body(cx, dest)
+  }
+}
@@ -0,0 +1,79 @@
+use futures_io::AsyncBufRead;
+use std::future::poll_fn;
+use std::marker::Unpin;
+use std::pin::Pin;
+use std::task::{ready, Poll};
+
+use crate::error::Error;
+use crate::head::{Builder, Head};
+use crate::machine::{exhausted, insert_lines, Budget, Terminator};
+use crate::options::RecognizeOptions;
+
+/// An alternative to [`recognize`] for readers that implement [`futures_io::AsyncBufRead`][buf-read].
+/// Rather than reading a handful of bytes at a time, the reader's buffer is scanned for the end of
+/// the head and only the bytes up to (and including) the terminating CR LF sequence are consumed;
+/// whatever follows remains buffered in the reader. The returned [`Head`] and errors are the same
+/// as those of [`recognize`].
+///
+/// ```rust
+/// use async_std::io::BufReader;
+/// use async_std::prelude::*;
+/// use async_std::task::block_on;
+/// use elaine::{recognize_buffered, RequestMethod};
+///
+/// block_on(async {
+///   let req: &[u8] = b"GET /elaine HTTP/1.1\r\nContent-Length: 3\r\n\r\nhey";
+///   let mut reader = BufReader::new(req);
+///   let head = recognize_buffered(&mut reader).await.unwrap();
+///   assert_eq!(head.method(), Some(RequestMethod::GET));
+///   let mut body = String::new();
+///   reader.read_to_string(&mut body).await.unwrap();
+///   assert_eq!(body, "hey");
+/// });
+/// ```
+///
+/// [`recognize`]: fn.recognize.html
+/// [`Head`]: struct.Head.html
+/// [buf-read]: https://docs.rs/futures-io/latest/futures_io/trait.AsyncBufRead.html
+pub async fn recognize_buffered<R>(reader: R) -> Result<Head, Error>
+where
+  R: AsyncBufRead + Unpin,
+{
+  recognize_buffered_with(reader, &RecognizeOptions::default()).await
+}
+
+/// Behaves like [`recognize_buffered`], using the provided options.
+///
+/// [`recognize_buffered`]: fn.recognize_buffered.html
+pub async fn recognize_buffered_with<R>(mut reader: R, options: &RecognizeOptions) -> Result<Head, Error>
+where
+  R: AsyncBufRead + Unpin,
+{
+  let mut budget = Budget::new(&options.limits);
+  let mut terminator = Terminator::new(options);
+  let mut head = Vec::new();
+
+  loop {
+    let (amount, done) = poll_fn(|cx| {
+      let available = ready!(Pin::new(&mut reader).poll_fill_buf(cx))?;
+      let (amount, done) = terminator.scan(available);
+      head.extend_from_slice(&available[0..amount]);
+      Pin::new(&mut reader).consume(amount);
+      Poll::Ready(Ok::<(usize, bool), std::io::Error>((amount, done)))
+    })
+    .await?;
+
+    budget.read(amount)?;
+
+    if done {
+      break;
+    }
+
+    if amount == 0 {
+      return Err(exhausted(&head));
+    }
+  }
+
+  let builder = insert_lines(&head, Builder::with_options(options), &mut budget)?;
+  Ok(builder.collect::<Head>())
+}
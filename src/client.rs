@@ -0,0 +1,113 @@
+//! A minimal HTTP/1.1 client, available with the `client` feature. A request [`Head`] is written
+//! to a connected stream and the response head is read with
+//! [`recognize_response`](../fn.recognize_response.html), never reading past its end, so the
+//! returned [`Body`] reads exactly the response body and the stream can be reused afterwards.
+//!
+//! ```rust,no_run
+//! use async_std::net::TcpStream;
+//! use async_std::prelude::*;
+//! use async_std::task::block_on;
+//! use elaine::{client, Builder, Head};
+//!
+//! block_on(async {
+//!   let stream = TcpStream::connect("127.0.0.1:8080").await.unwrap();
+//!   let head = Builder::new()
+//!     .insert("GET /status HTTP/1.1".to_string())
+//!     .and_then(|builder| builder.insert("Host: 127.0.0.1".to_string()))
+//!     .unwrap()
+//!     .collect::<Head>();
+//!
+//!   let (response, mut body) = client::send(&stream, &head, b"").await.unwrap();
+//!   let mut contents = String::new();
+//!   body.read_to_string(&mut contents).await.unwrap();
+//!   println!("{} {}", response.status().unwrap_or_default(), contents);
+//! });
+//! ```
+//!
+//! [`Head`]: ../struct.Head.html
+//! [`Body`]: ../struct.Body.html
+use async_std::io::{copy, sink};
+use async_std::net::TcpStream;
+
+use crate::body::Body;
+use crate::error::{Error, Position};
+use crate::framing::{BodyFraming, CONTENT_LENGTH};
+use crate::head::{Head, RequestMethod, RequestVersion};
+use crate::options::RecognizeOptions;
+use crate::recognize::{recognize_response_with, write_all};
+use crate::response::ResponseHead;
+
+/// Writes the head and body of a request to the stream and reads the response head. The body has to
+/// match the framing of the head: a `Content-Length` is added for non-empty bodies of heads without
+/// one, a different `Content-Length` fails with `Error::InvalidContentLength`, and bodies of chunked
+/// requests have to be complete chunked encodings or fail with `Error::InvalidChunk`. Interim `1xx` responses other than `101 Switching Protocols` are skipped, and folded header
+/// values of the response are unfolded.
+pub async fn send<'a>(
+  stream: &'a TcpStream,
+  head: &Head,
+  body: &[u8],
+) -> Result<(ResponseHead, Body<&'a TcpStream>), Error> {
+  send_with(stream, head, body, &RecognizeOptions::response()).await
+}
+
+/// Like [`send`](fn.send.html), reading the response head with the given options.
+pub async fn send_with<'a>(
+  stream: &'a TcpStream,
+  head: &Head,
+  body: &[u8],
+  options: &RecognizeOptions,
+) -> Result<(ResponseHead, Body<&'a TcpStream>), Error> {
+  let mut request = serialize(head, body).await?;
+  request.extend_from_slice(body);
+  write_all(stream, &request).await?;
+
+  let mut reader = stream;
+
+  let response = loop {
+    let response = recognize_response_with(&mut reader, options).await?;
+
+    match response.status() {
+      Some(status) if (100..200).contains(&status) && status != 101 => continue,
+      _ => break response,
+    }
+  };
+
+  let body = if head.method() == Some(RequestMethod::HEAD) {
+    Body::sized(reader, 0)
+  } else {
+    Body::response(&response, reader)
+  };
+
+  Ok((response, body))
+}
+
+// Serializes the head, checking that the body matches its framing so that the request cannot run
+// into the next one on the connection.
+async fn serialize(head: &Head, body: &[u8]) -> Result<Vec<u8>, Error> {
+  let mut request = head.to_bytes();
+
+  match head.framing() {
+    BodyFraming::None if body.is_empty() => (),
+    BodyFraming::None if head.version() == Some(RequestVersion::Simple) => {
+      return Err(Error::InvalidContentLength(Position::default()));
+    }
+    BodyFraming::None => {
+      request.truncate(request.len() - 2);
+      request.extend_from_slice(format!("{}: {}\r\n\r\n", CONTENT_LENGTH, body.len()).as_bytes());
+    }
+    BodyFraming::Length(len) if len == body.len() => (),
+    BodyFraming::Length(_) => return Err(Error::InvalidContentLength(Position::default())),
+    BodyFraming::Chunked => {
+      let mut rest = body;
+      let mut chunks = Body::chunked(&mut rest);
+      let decoded = copy(&mut chunks, &mut sink()).await;
+
+      if decoded.is_err() || !chunks.is_done() || !rest.is_empty() {
+        return Err(Error::InvalidChunk);
+      }
+    }
+    BodyFraming::CloseDelimited => return Err(Error::InvalidTransferEncoding),
+  }
+
+  Ok(request)
+}
@@ -0,0 +1,168 @@
+use futures_io::AsyncRead;
+use std::marker::Unpin;
+
+use crate::body::Body;
+use crate::error::Error;
+use crate::head::{Head, RequestVersion};
+use crate::options::RecognizeOptions;
+use crate::recognize::{read_some, recognize_with};
+
+/// Returns true when the connection a request was read from may be reused for another request,
+/// following the [persistence rules][persistence] of the request's version and `Connection` header.
+/// A malformed `Connection` header never allows the connection to be reused.
+///
+/// [persistence]: https://tools.ietf.org/html/rfc7230#section-6.3
+pub(crate) fn is_persistent(head: &Head) -> bool {
+  let options = match head.connection() {
+    Ok(options) => options,
+    Err(_) => return false,
+  };
+
+  match head.version() {
+    Some(RequestVersion::RFC2616) => !options.iter().any(|option| option == "close"),
+    Some(RequestVersion::RFC1945) => options.iter().any(|option| option == "keep-alive"),
+    Some(RequestVersion::Simple) | None => false,
+  }
+}
+
+/// Reads the requests sent over a single connection one after another, for as long as the client
+/// keeps the connection alive. Each request is returned with a [`Body`] reader; whatever part of the
+/// body is left unread is skipped before the next head is read. Neither the heads nor the bodies are
+/// ever read past their end, so pipelined requests are returned in order.
+///
+/// ```rust
+/// use async_std::prelude::*;
+/// use async_std::task::block_on;
+/// use elaine::Connection;
+///
+/// block_on(async {
+///   let reqs: &[u8] = b"POST /a HTTP/1.1\r\nContent-Length: 3\r\n\r\nheyGET /b HTTP/1.1\r\nConnection: close\r\n\r\n";
+///   let mut connection = Connection::new(reqs);
+///   let mut paths = Vec::new();
+///
+///   while let Some(result) = connection.next().await {
+///     let (head, _body) = result.unwrap();
+///     paths.push(head.path().unwrap());
+///   }
+///
+///   assert_eq!(paths, vec!["/a", "/b"]);
+/// });
+/// ```
+///
+/// [`Body`]: struct.Body.html
+#[derive(Debug)]
+pub struct Connection<R> {
+  body: Option<Body<R>>,
+  options: RecognizeOptions,
+  persistent: bool,
+}
+
+impl<R> Connection<R>
+where
+  R: AsyncRead + Unpin,
+{
+  pub fn new(reader: R) -> Self {
+    Connection::with_options(reader, RecognizeOptions::default())
+  }
+
+  pub fn with_options(reader: R, options: RecognizeOptions) -> Self {
+    Connection {
+      body: Some(Body::sized(reader, 0)),
+      options,
+      persistent: true,
+    }
+  }
+
+  /// Whether another request will be read after the current one; false once the client asked for
+  /// the connection to be closed, or after [`close`] was called.
+  ///
+  /// [`close`]: #method.close
+  pub fn is_persistent(&self) -> bool {
+    self.persistent && self.body.is_some()
+  }
+
+  /// Stops reading requests after the current one, e.g. when the response is sent with a
+  /// `Connection: close` header.
+  pub fn close(&mut self) {
+    self.persistent = false;
+  }
+
+  /// Reads the next request, after skipping the unread remainder of the previous body. Returns
+  /// `None` once the connection is no longer persistent, or when the client closes the connection
+  /// between requests. Errors are final; no further requests are read after one is returned.
+  pub async fn next(&mut self) -> Option<Result<(Head, &mut Body<R>), Error>> {
+    if !self.persistent {
+      return None;
+    }
+
+    let mut body = self.body.take()?;
+
+    if let Err(e) = skip(&mut body).await {
+      return Some(Err(e));
+    }
+
+    let mut reader = body.into_inner();
+    let head = match recognize_with(&mut reader, &self.options).await {
+      Ok(head) => head,
+      // A connection closed before the first byte of a head simply ends the sequence of requests.
+      Err(Error::UnexpectedEof(position)) if position.offset == 0 => {
+        self.persistent = false;
+        self.body = Some(Body::sized(reader, 0));
+        return None;
+      }
+      Err(e) => return Some(Err(e)),
+    };
+
+    self.persistent = is_persistent(&head);
+
+    match Body::new(&head, reader) {
+      Ok(body) => Some(Ok((head, self.body.insert(body)))),
+      Err(e) => Some(Err(e)),
+    }
+  }
+
+  /// Returns the underlying reader, positioned after the last body that was read or skipped
+  /// completely; `None` after an error.
+  pub fn into_inner(self) -> Option<R> {
+    self.body.map(Body::into_inner)
+  }
+}
+
+/// Reads and discards whatever is left of a body.
+pub(crate) async fn skip<R>(body: &mut Body<R>) -> Result<(), Error>
+where
+  R: AsyncRead + Unpin,
+{
+  let mut scratch = [0u8; 1024];
+
+  while !body.is_done() {
+    if read_some(body, &mut scratch).await? == 0 {
+      break;
+    }
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod test {
+  use super::is_persistent;
+  use crate::head::{Builder, Head};
+
+  fn head(lines: &[&str]) -> Head {
+    lines
+      .iter()
+      .fold(Builder::new(), |builder, line| {
+        builder.insert(line.to_string()).unwrap()
+      })
+      .collect::<Head>()
+  }
+
+  #[test]
+  fn test_persistence() {
+    assert!(is_persistent(&head(&["GET / HTTP/1.1"])));
+    assert!(!is_persistent(&head(&["GET / HTTP/1.1", "Connection: Upgrade, close"])));
+    assert!(!is_persistent(&head(&["GET / HTTP/1.0"])));
+    assert!(is_persistent(&head(&["GET / HTTP/1.0", "Connection: Keep-Alive"])));
+  }
+}
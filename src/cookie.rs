@@ -0,0 +1,362 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::error::Error;
+use crate::head::{is_token, OWS};
+use crate::headers::GetAll;
+
+pub(crate) const COOKIE: &str = "Cookie";
+
+pub(crate) const SET_COOKIE: &str = "Set-Cookie";
+
+/// An iterator over the name and value of every cookie sent in the `Cookie` headers of a request,
+/// returned by [`Head::cookies`]. Values enclosed in double quotes are returned without them and
+/// pairs that are not of the form `name=value` are skipped.
+///
+/// ```rust
+/// use elaine::blocking::recognize;
+///
+/// let req: &[u8] = b"GET / HTTP/1.1\r\nCookie: sid=31d4d96e; lang=\"en-US\"\r\nCookie: theme=dark\r\n\r\n";
+/// let head = recognize(req).unwrap();
+///
+/// assert_eq!(
+///   head.cookies().collect::<Vec<_>>(),
+///   vec![("sid", "31d4d96e"), ("lang", "en-US"), ("theme", "dark")]
+/// );
+/// ```
+///
+/// [`Head::cookies`]: struct.Head.html#method.cookies
+#[derive(Debug, Clone)]
+pub struct Cookies<'a> {
+  headers: GetAll<'a>,
+  pairs: Option<std::str::Split<'a, char>>,
+}
+
+impl<'a> Cookies<'a> {
+  pub(crate) fn new(headers: GetAll<'a>) -> Self {
+    Cookies { headers, pairs: None }
+  }
+}
+
+impl<'a> Iterator for Cookies<'a> {
+  type Item = (&'a str, &'a str);
+
+  fn next(&mut self) -> Option<(&'a str, &'a str)> {
+    loop {
+      let pair = match self.pairs.as_mut().and_then(Iterator::next) {
+        Some(pair) => pair,
+        None => {
+          self.pairs = Some(self.headers.next()?.split(';'));
+          continue;
+        }
+      };
+
+      if let Some(cookie) = parse_pair(pair) {
+        return Some(cookie);
+      }
+    }
+  }
+}
+
+fn parse_pair(pair: &str) -> Option<(&str, &str)> {
+  let (name, value) = pair.trim_matches(OWS).split_once('=')?;
+  let name = name.trim_end_matches(OWS);
+  let value = unquote(value.trim_start_matches(OWS));
+
+  match is_token(name) && value.bytes().all(is_cookie_octet) {
+    true => Some((name, value)),
+    false => None,
+  }
+}
+
+// A cookie value may be wrapped in double quotes, which are not part of the value.
+fn unquote(value: &str) -> &str {
+  match value.strip_prefix('"').and_then(|value| value.strip_suffix('"')) {
+    Some(unquoted) => unquoted,
+    None => value,
+  }
+}
+
+// The characters allowed in cookie values by RFC 6265 §4.1.1; no whitespace, double quotes,
+// commas, semicolons or backslashes.
+fn is_cookie_octet(b: u8) -> bool {
+  matches!(b, 0x21 | 0x23..=0x2B | 0x2D..=0x3A | 0x3C..=0x5B | 0x5D..=0x7E)
+}
+
+/// The `SameSite` attribute of a [`SetCookie`]. Browsers ignore cookies with `SameSite=None` unless
+/// they are also `Secure`.
+///
+/// [`SetCookie`]: struct.SetCookie.html
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SameSite {
+  Strict,
+  Lax,
+  None,
+}
+
+impl std::fmt::Display for SameSite {
+  fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+    match self {
+      SameSite::Strict => write!(formatter, "Strict"),
+      SameSite::Lax => write!(formatter, "Lax"),
+      SameSite::None => write!(formatter, "None"),
+    }
+  }
+}
+
+/// Builds the value of a `Set-Cookie` response header, per [RFC 6265 §4.1][set-cookie]. The name,
+/// value, domain and path are checked as they are set, failing with an `Error::InvalidHeaderValue`
+/// when they contain characters that cannot be sent. A value wrapped in double quotes is sent with
+/// its quotes.
+///
+/// ```rust
+/// use elaine::{SameSite, SetCookie};
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let cookie = SetCookie::new("sid", "31d4d96e")?
+///   .path("/")?
+///   .expires(UNIX_EPOCH + Duration::from_secs(1445412480))
+///   .max_age(Duration::from_secs(3600))
+///   .secure()
+///   .http_only()
+///   .same_site(SameSite::Lax);
+///
+/// assert_eq!(
+///   cookie.to_string(),
+///   "sid=31d4d96e; Expires=Wed, 21 Oct 2015 07:28:00 GMT; Max-Age=3600; Path=/; Secure; HttpOnly; SameSite=Lax"
+/// );
+/// # Ok::<(), elaine::Error>(())
+/// ```
+///
+/// [set-cookie]: https://tools.ietf.org/html/rfc6265#section-4.1
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetCookie {
+  name: String,
+  value: String,
+  expires: Option<SystemTime>,
+  max_age: Option<Duration>,
+  domain: Option<String>,
+  path: Option<String>,
+  secure: bool,
+  http_only: bool,
+  same_site: Option<SameSite>,
+}
+
+impl SetCookie {
+  pub fn new<N, V>(name: N, value: V) -> Result<Self, Error>
+  where
+    N: Into<String>,
+    V: Into<String>,
+  {
+    let (name, value) = (name.into(), value.into());
+
+    if !is_token(&name) || !unquote(&value).bytes().all(is_cookie_octet) {
+      return Err(Error::InvalidHeaderValue(SET_COOKIE));
+    }
+
+    Ok(SetCookie {
+      name,
+      value,
+      expires: None,
+      max_age: None,
+      domain: None,
+      path: None,
+      secure: false,
+      http_only: false,
+      same_site: None,
+    })
+  }
+
+  /// A cookie with an empty value that expires immediately, telling the client to remove the cookie
+  /// of the same name; its domain and path have to match those of the cookie being removed.
+  pub fn removal<N>(name: N) -> Result<Self, Error>
+  where
+    N: Into<String>,
+  {
+    Ok(SetCookie::new(name, "")?.expires(UNIX_EPOCH).max_age(Duration::ZERO))
+  }
+
+  /// Sets the `Expires` attribute; times before 1970 are sent as the start of 1970.
+  pub fn expires(self, expires: SystemTime) -> Self {
+    SetCookie {
+      expires: Some(expires),
+      ..self
+    }
+  }
+
+  /// Sets the `Max-Age` attribute, in whole seconds.
+  pub fn max_age(self, max_age: Duration) -> Self {
+    SetCookie {
+      max_age: Some(max_age),
+      ..self
+    }
+  }
+
+  pub fn domain<D>(self, domain: D) -> Result<Self, Error>
+  where
+    D: Into<String>,
+  {
+    Ok(SetCookie {
+      domain: Some(attribute(domain.into())?),
+      ..self
+    })
+  }
+
+  pub fn path<P>(self, path: P) -> Result<Self, Error>
+  where
+    P: Into<String>,
+  {
+    Ok(SetCookie {
+      path: Some(attribute(path.into())?),
+      ..self
+    })
+  }
+
+  pub fn secure(self) -> Self {
+    SetCookie { secure: true, ..self }
+  }
+
+  pub fn http_only(self) -> Self {
+    SetCookie {
+      http_only: true,
+      ..self
+    }
+  }
+
+  pub fn same_site(self, same_site: SameSite) -> Self {
+    SetCookie {
+      same_site: Some(same_site),
+      ..self
+    }
+  }
+
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  pub fn value(&self) -> &str {
+    &self.value
+  }
+}
+
+/// Formats the cookie as the value of a `Set-Cookie` header.
+impl std::fmt::Display for SetCookie {
+  fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+    write!(formatter, "{}={}", self.name, self.value)?;
+
+    if let Some(expires) = self.expires {
+      write!(formatter, "; Expires={}", http_date(expires))?;
+    }
+    if let Some(max_age) = self.max_age {
+      write!(formatter, "; Max-Age={}", max_age.as_secs())?;
+    }
+    if let Some(domain) = &self.domain {
+      write!(formatter, "; Domain={}", domain)?;
+    }
+    if let Some(path) = &self.path {
+      write!(formatter, "; Path={}", path)?;
+    }
+    if self.secure {
+      write!(formatter, "; Secure")?;
+    }
+    if self.http_only {
+      write!(formatter, "; HttpOnly")?;
+    }
+    if let Some(same_site) = self.same_site {
+      write!(formatter, "; SameSite={}", same_site)?;
+    }
+
+    Ok(())
+  }
+}
+
+// Attribute values may hold any visible character or space other than a semicolon.
+fn attribute(value: String) -> Result<String, Error> {
+  match value.bytes().all(|b| (0x20..0x7F).contains(&b) && b != b';') {
+    true => Ok(value),
+    false => Err(Error::InvalidHeaderValue(SET_COOKIE)),
+  }
+}
+
+/// Formats a time as an [IMF-fixdate][date], e.g. `Sun, 06 Nov 1994 08:49:37 GMT`.
+///
+/// [date]: https://tools.ietf.org/html/rfc7231#section-7.1.1.1
+pub(crate) fn http_date(time: SystemTime) -> String {
+  const DAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+  const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+  ];
+
+  let seconds = time
+    .duration_since(UNIX_EPOCH)
+    .map(|since| since.as_secs())
+    .unwrap_or(0);
+  let (days, rest) = (seconds / 86400, seconds % 86400);
+
+  // Converts days since 1970 into a civil date, counting in 400 year eras starting in March so
+  // that leap days fall at the end of each year.
+  let shifted = days + 719_468;
+  let (era, day_of_era) = (shifted / 146_097, shifted % 146_097);
+  let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+  let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+  let month = (5 * day_of_year + 2) / 153;
+  let day = day_of_year - (153 * month + 2) / 5 + 1;
+  let month = if month < 10 { month + 2 } else { month - 10 };
+  let year = era * 400 + year_of_era + u64::from(month < 2);
+
+  format!(
+    "{}, {:02} {} {} {:02}:{:02}:{:02} GMT",
+    DAYS[(days % 7) as usize],
+    day,
+    MONTHS[month as usize],
+    year,
+    rest / 3600,
+    rest % 3600 / 60,
+    rest % 60
+  )
+}
+
+#[cfg(test)]
+mod test {
+  use super::{http_date, parse_pair, SetCookie};
+  use std::time::{Duration, UNIX_EPOCH};
+
+  #[test]
+  fn test_parse_pair() {
+    assert_eq!(parse_pair(" sid = abc "), Some(("sid", "abc")));
+    assert_eq!(parse_pair("sid=\"abc\""), Some(("sid", "abc")));
+    assert_eq!(parse_pair("sid="), Some(("sid", "")));
+    assert_eq!(parse_pair("sid"), None);
+    assert_eq!(parse_pair("=abc"), None);
+    assert_eq!(parse_pair("sid=a b"), None);
+    assert_eq!(parse_pair("sid=\"abc"), None);
+  }
+
+  #[test]
+  fn test_http_date() {
+    assert_eq!(http_date(UNIX_EPOCH), "Thu, 01 Jan 1970 00:00:00 GMT");
+    let at = |seconds| http_date(UNIX_EPOCH + Duration::from_secs(seconds));
+    assert_eq!(at(784111777), "Sun, 06 Nov 1994 08:49:37 GMT");
+    assert_eq!(at(951782400), "Tue, 29 Feb 2000 00:00:00 GMT");
+    assert_eq!(at(4107542400), "Mon, 01 Mar 2100 00:00:00 GMT");
+  }
+
+  #[test]
+  fn test_set_cookie() {
+    let cookie = SetCookie::new("id", "a3fWa").unwrap().domain("example.com").unwrap();
+    assert_eq!(cookie.to_string(), "id=a3fWa; Domain=example.com");
+    assert_eq!(
+      SetCookie::removal("id").unwrap().to_string(),
+      "id=; Expires=Thu, 01 Jan 1970 00:00:00 GMT; Max-Age=0"
+    );
+
+    assert!(SetCookie::new("i d", "a").is_err());
+    assert!(SetCookie::new("id", "a;b").is_err());
+    assert_eq!(SetCookie::new("id", "\"a3fWa\"").unwrap().to_string(), "id=\"a3fWa\"");
+    assert_eq!(SetCookie::new("id", "\"\"").unwrap().to_string(), "id=\"\"");
+    assert!(SetCookie::new("id", "\"").is_err());
+    assert!(SetCookie::new("id", "a\"b").is_err());
+    assert!(SetCookie::new("id", "\"a\"b\"").is_err());
+    assert!(SetCookie::new("id", "a").unwrap().path("/a;b").is_err());
+    assert!(SetCookie::new("id", "a").unwrap().domain("a\r\nb").is_err());
+  }
+}
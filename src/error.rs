@@ -0,0 +1,206 @@
+use std::io::ErrorKind;
+
+use crate::options::{LimitExceeded, Timeout};
+
+/// The location in the head at which a parsing error occurred. Lines are counted starting from
+/// one and the offset is the number of bytes preceding the line (or, for errors that are not tied
+/// to a complete line, the number of bytes read before the failure).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+  pub offset: usize,
+  pub line: usize,
+}
+
+impl Default for Position {
+  fn default() -> Self {
+    Position { offset: 0, line: 1 }
+  }
+}
+
+impl Position {
+  // Moves past a line that was read, accounting for its CR LF terminator.
+  pub(crate) fn advance(&mut self, length: usize) {
+    self.offset += length + 2;
+    self.line += 1;
+  }
+}
+
+impl std::fmt::Display for Position {
+  fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+    write!(formatter, "line {} (offset {})", self.line, self.offset)
+  }
+}
+
+/// A malformed header line skipped while parsing with [`HeaderParsing::Lenient`].
+///
+/// [`HeaderParsing::Lenient`]: enum.HeaderParsing.html#variant.Lenient
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+  pub position: Position,
+  pub line: String,
+}
+
+/// A head that did not arrive within its [`Timeouts`], with everything that was read before the
+/// timeout elapsed.
+///
+/// [`Timeouts`]: struct.Timeouts.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimedOut {
+  pub timeout: Timeout,
+  pub position: Position,
+  /// The bytes read so far, decoded lossily.
+  pub partial: String,
+}
+
+/// Errors produced while reading and parsing http messages. Converts to and from
+/// `std::io::Error`, so it can be propagated with `?` from functions returning either. New variants
+/// may be added in minor releases.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+  InvalidMethod(Position),
+  InvalidVersion(Position),
+  InvalidRequestLine(Position),
+  InvalidRequestTarget(Position),
+  InvalidStatusLine(Position),
+  InvalidHeader(Position),
+  InvalidUtf8(Position),
+  InvalidContentLength(Position),
+  InvalidFraming(Position),
+  UnexpectedEof(Position),
+  LimitExceeded(LimitExceeded, Position),
+  TimedOut(TimedOut),
+  /// The reader started with the [HTTP/2 connection preface](constant.HTTP2_PREFACE.html), which
+  /// has been consumed in its entirety; the rest of the connection speaks HTTP/2.
+  Http2Preface,
+  /// A header needed by a typed accessor of [`Head`](struct.Head.html) is malformed, or repeated
+  /// where only one field is allowed; holds the name of the header.
+  InvalidHeaderValue(&'static str),
+  InvalidChunk,
+  /// A WebSocket frame broke the protocol; holds the close code to send to the peer.
+  InvalidFrame(u16),
+  InvalidTransferEncoding,
+  BodyTooLarge,
+  Io(std::io::Error),
+}
+
+impl Error {
+  /// The position at which parsing failed, for errors raised while reading a head.
+  pub fn position(&self) -> Option<Position> {
+    match self {
+      Error::InvalidMethod(position)
+      | Error::InvalidVersion(position)
+      | Error::InvalidRequestLine(position)
+      | Error::InvalidRequestTarget(position)
+      | Error::InvalidStatusLine(position)
+      | Error::InvalidHeader(position)
+      | Error::InvalidUtf8(position)
+      | Error::InvalidContentLength(position)
+      | Error::InvalidFraming(position)
+      | Error::UnexpectedEof(position)
+      | Error::LimitExceeded(_, position) => Some(*position),
+      Error::TimedOut(timed_out) => Some(timed_out.position),
+      Error::Http2Preface
+      | Error::InvalidHeaderValue(_)
+      | Error::InvalidChunk
+      | Error::InvalidFrame(_)
+      | Error::InvalidTransferEncoding
+      | Error::BodyTooLarge
+      | Error::Io(_) => None,
+    }
+  }
+
+  // Replaces the position of a positioned error; used by callers that know more about where
+  // the failing input came from than the function that raised it.
+  pub(crate) fn at(self, position: Position) -> Self {
+    match self {
+      Error::InvalidMethod(_) => Error::InvalidMethod(position),
+      Error::InvalidVersion(_) => Error::InvalidVersion(position),
+      Error::InvalidRequestLine(_) => Error::InvalidRequestLine(position),
+      Error::InvalidRequestTarget(_) => Error::InvalidRequestTarget(position),
+      Error::InvalidStatusLine(_) => Error::InvalidStatusLine(position),
+      Error::InvalidHeader(_) => Error::InvalidHeader(position),
+      Error::InvalidUtf8(_) => Error::InvalidUtf8(position),
+      Error::InvalidContentLength(_) => Error::InvalidContentLength(position),
+      Error::InvalidFraming(_) => Error::InvalidFraming(position),
+      Error::UnexpectedEof(_) => Error::UnexpectedEof(position),
+      Error::LimitExceeded(limit, _) => Error::LimitExceeded(limit, position),
+      Error::TimedOut(timed_out) => Error::TimedOut(TimedOut { position, ..timed_out }),
+      other => other,
+    }
+  }
+
+  fn kind(&self) -> ErrorKind {
+    match self {
+      Error::UnexpectedEof(_) => ErrorKind::UnexpectedEof,
+      Error::TimedOut(_) => ErrorKind::TimedOut,
+      Error::Io(e) => e.kind(),
+      _ => ErrorKind::InvalidData,
+    }
+  }
+}
+
+impl std::fmt::Display for Error {
+  fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+    match self {
+      Error::InvalidMethod(position) => write!(formatter, "Unable to parse request method at {}", position),
+      Error::InvalidVersion(position) => write!(formatter, "Unmatched http version at {}", position),
+      Error::InvalidRequestLine(position) => write!(formatter, "Invalid request line at {}", position),
+      Error::InvalidRequestTarget(position) => write!(formatter, "Invalid request target at {}", position),
+      Error::InvalidStatusLine(position) => write!(formatter, "Invalid status line at {}", position),
+      Error::InvalidHeader(position) => write!(formatter, "Invalid header line at {}", position),
+      Error::InvalidUtf8(position) => write!(formatter, "Invalid utf-8 sequence at {}", position),
+      Error::InvalidContentLength(position) => write!(formatter, "Invalid content length at {}", position),
+      Error::InvalidFraming(position) => write!(formatter, "Ambiguous message framing at {}", position),
+      Error::UnexpectedEof(position) => write!(
+        formatter,
+        "Reader exhausted before terminating HTTP head at {}",
+        position
+      ),
+      Error::LimitExceeded(limit, position) => write!(formatter, "{} at {}", limit, position),
+      Error::TimedOut(timed_out) => write!(formatter, "{} at {}", timed_out.timeout, timed_out.position),
+      Error::Http2Preface => write!(formatter, "Received HTTP/2 connection preface"),
+      Error::InvalidHeaderValue(name) => write!(formatter, "Invalid {} header value", name),
+      Error::InvalidChunk => write!(formatter, "Invalid chunked transfer coding"),
+      Error::InvalidFrame(code) => write!(formatter, "Invalid WebSocket frame (close code {})", code),
+      Error::InvalidTransferEncoding => write!(formatter, "Unsupported transfer encoding"),
+      Error::BodyTooLarge => write!(formatter, "Body exceeds maximum size"),
+      Error::Io(e) => write!(formatter, "{}", e),
+    }
+  }
+}
+
+impl std::error::Error for Error {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      Error::Io(e) => Some(e),
+      _ => None,
+    }
+  }
+}
+
+impl From<std::io::Error> for Error {
+  fn from(error: std::io::Error) -> Self {
+    // Errors that were converted into an `std::io::Error` on their way through a reader are
+    // unwrapped back into their original form.
+    if error.get_ref().map(|inner| inner.is::<Error>()) != Some(true) {
+      return Error::Io(error);
+    }
+
+    let kind = error.kind();
+    match error.into_inner().map(|inner| inner.downcast::<Error>()) {
+      Some(Ok(inner)) => *inner,
+      Some(Err(inner)) => Error::Io(std::io::Error::new(kind, inner)),
+      None => Error::Io(std::io::Error::from(kind)),
+    }
+  }
+}
+
+impl From<Error> for std::io::Error {
+  fn from(error: Error) -> Self {
+    match error {
+      Error::Io(e) => e,
+      other => std::io::Error::new(other.kind(), other),
+    }
+  }
+}
@@ -0,0 +1,97 @@
+use crate::error::{Error, Position};
+use crate::headers::Headers;
+
+/// How the end of a message body is found, per the [message length][length] rules of RFC 7230.
+///
+/// ```rust
+/// use async_std::task::block_on;
+/// use elaine::{recognize, BodyFraming, Error};
+///
+/// block_on(async {
+///   let mut req: &[u8] = b"POST / HTTP/1.1\r\nTransfer-Encoding: gzip, chunked\r\n\r\n";
+///   assert_eq!(recognize(&mut req).await.unwrap().framing(), BodyFraming::Chunked);
+///
+///   let mut req: &[u8] = b"POST / HTTP/1.1\r\nContent-Length: 3\r\nTransfer-Encoding: chunked\r\n\r\n";
+///   assert!(matches!(recognize(&mut req).await, Err(Error::InvalidFraming(_))));
+/// });
+/// ```
+///
+/// [length]: https://tools.ietf.org/html/rfc7230#section-3.3.3
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BodyFraming {
+  /// The message has no body.
+  None,
+  /// The body is exactly this many bytes, from a `Content-Length`.
+  Length(usize),
+  /// The body uses the chunked transfer coding.
+  Chunked,
+  /// The body continues until the connection is closed; only possible for responses.
+  CloseDelimited,
+}
+
+pub(crate) const CONTENT_LENGTH: &str = "Content-Length";
+
+pub(crate) const TRANSFER_ENCODING: &str = "Transfer-Encoding";
+
+/// The transfer codings applied to the message, in order, from every `Transfer-Encoding` field.
+pub(crate) fn codings(headers: &Headers) -> impl Iterator<Item = String> + '_ {
+  headers
+    .get_all(TRANSFER_ENCODING)
+    .flat_map(|value| value.split(','))
+    .map(|coding| coding.trim().to_ascii_lowercase())
+    .filter(|coding| !coding.is_empty())
+}
+
+/// Returns whether the transfer codings end in chunked, or `None` when there are no codings.
+pub(crate) fn chunked(headers: &Headers) -> Option<bool> {
+  codings(headers).last().map(|last| last == "chunked")
+}
+
+/// Parses a `Content-Length` value, which may be a list of identical lengths from a sender that
+/// combined duplicate fields.
+pub(crate) fn parse_content_length(value: &str) -> Result<usize, Error> {
+  let invalid = || Error::InvalidContentLength(Position::default());
+  let mut length = None;
+
+  for part in value.split(',').map(|part| part.trim()) {
+    if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+      return Err(invalid());
+    }
+
+    let parsed = part.parse::<usize>().map_err(|_| invalid())?;
+
+    if length.is_some_and(|length| length != parsed) {
+      return Err(invalid());
+    }
+
+    length = Some(parsed);
+  }
+
+  length.ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod test {
+  use super::{chunked, parse_content_length};
+  use crate::headers::Headers;
+
+  #[test]
+  fn test_content_length() {
+    assert_eq!(parse_content_length("42").unwrap(), 42);
+    assert_eq!(parse_content_length("42, 42").unwrap(), 42);
+    assert!(parse_content_length("42, 43").is_err());
+    assert!(parse_content_length("+42").is_err());
+    assert!(parse_content_length("").is_err());
+    assert!(parse_content_length("4 2").is_err());
+  }
+
+  #[test]
+  fn test_chunked() {
+    let mut headers = Headers::new();
+    assert_eq!(chunked(&headers), None);
+    headers.append("Transfer-Encoding", "chunked");
+    assert_eq!(chunked(&headers), Some(true));
+    headers.append("transfer-encoding", "GZIP");
+    assert_eq!(chunked(&headers), Some(false));
+  }
+}
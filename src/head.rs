@@ -1,4 +1,11 @@
-use std::io::{Error, ErrorKind};
+use crate::cookie::{Cookies, COOKIE};
+use crate::credentials::{Credentials, AUTHORIZATION, PROXY_AUTHORIZATION};
+use crate::error::{Diagnostic, Error, Position};
+use crate::framing::{chunked, codings, parse_content_length, BodyFraming, CONTENT_LENGTH, TRANSFER_ENCODING};
+use crate::headers::Headers;
+use crate::options::{HeaderParsing, ObsFold, RecognizeOptions};
+use crate::target::RequestTarget;
+use crate::values::{Host, MediaType, CONNECTION, CONTENT_TYPE, HOST, ORIGIN, REFERER, USER_AGENT};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum RequestMethod {
@@ -11,6 +18,21 @@ pub enum RequestMethod {
   PUT,
   PATCH,
   TRACE,
+  /// Any other method that is a valid [token][token], e.g. `PROPFIND` or `PURGE`.
+  ///
+  /// [token]: https://tools.ietf.org/html/rfc7230#section-3.2.6
+  Extension(String),
+}
+
+pub(crate) fn is_tchar(c: char) -> bool {
+  match c {
+    '!' | '#' | '$' | '%' | '&' | '\'' | '*' | '+' | '-' | '.' | '^' | '_' | '`' | '|' | '~' => true,
+    c => c.is_ascii_alphanumeric(),
+  }
+}
+
+pub(crate) fn is_token(input: &str) -> bool {
+  !input.is_empty() && input.chars().all(is_tchar)
 }
 
 impl RequestMethod {
@@ -28,20 +50,70 @@ impl RequestMethod {
       "PUT" => Ok(RequestMethod::PUT),
       "PATCH" => Ok(RequestMethod::PATCH),
       "TRACE" => Ok(RequestMethod::TRACE),
-      _ => {
-        return Err(Error::new(
-          ErrorKind::InvalidData,
-          format!("Unable to parse request method"),
-        ))
-      }
+      other if is_token(other) => Ok(RequestMethod::Extension(String::from(other))),
+      _ => Err(Error::InvalidMethod(Position::default())),
     }
   }
+
+  /// Returns true for the methods defined as [safe][safe]; extension methods are never considered
+  /// safe since their semantics are unknown.
+  ///
+  /// [safe]: https://tools.ietf.org/html/rfc7231#section-4.2.1
+  pub fn is_safe(&self) -> bool {
+    matches!(
+      self,
+      RequestMethod::GET | RequestMethod::HEAD | RequestMethod::OPTIONS | RequestMethod::TRACE
+    )
+  }
+
+  /// Returns true for the methods defined as [idempotent][idem], which includes every safe method.
+  ///
+  /// [idem]: https://tools.ietf.org/html/rfc7231#section-4.2.2
+  pub fn is_idempotent(&self) -> bool {
+    self.is_safe() || matches!(self, RequestMethod::PUT | RequestMethod::DELETE)
+  }
+}
+
+impl std::fmt::Display for RequestMethod {
+  fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+    let name = match self {
+      RequestMethod::CONNECT => "CONNECT",
+      RequestMethod::DELETE => "DELETE",
+      RequestMethod::GET => "GET",
+      RequestMethod::HEAD => "HEAD",
+      RequestMethod::OPTIONS => "OPTIONS",
+      RequestMethod::POST => "POST",
+      RequestMethod::PUT => "PUT",
+      RequestMethod::PATCH => "PATCH",
+      RequestMethod::TRACE => "TRACE",
+      RequestMethod::Extension(name) => name.as_str(),
+    };
+    write!(formatter, "{}", name)
+  }
 }
 
+/// The [HTTP/2 connection preface][preface] sent by clients with prior knowledge of HTTP/2 support.
+/// When [`recognize`] reads it, an [`Error::Http2Preface`] is returned after consuming exactly these
+/// bytes, which an HTTP/2 implementation expecting to read the preface itself should be given first.
+///
+/// [preface]: https://tools.ietf.org/html/rfc7540#section-3.5
+/// [`recognize`]: fn.recognize.html
+/// [`Error::Http2Preface`]: enum.Error.html#variant.Http2Preface
+pub const HTTP2_PREFACE: &[u8] = PREFACE.as_bytes();
+
+pub(crate) const PREFACE: &str = "PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+pub(crate) const PREFACE_LINE: &str = "PRI * HTTP/2.0";
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum RequestVersion {
   RFC2616,
   RFC1945,
+  /// An HTTP/0.9 simple request, consisting of only a `GET` request line; see
+  /// [`RecognizeOptions::http09`].
+  ///
+  /// [`RecognizeOptions::http09`]: struct.RecognizeOptions.html#structfield.http09
+  Simple,
 }
 
 impl RequestVersion {
@@ -52,13 +124,21 @@ impl RequestVersion {
     match format!("{}", input).as_str() {
       "HTTP/1.1" => Ok(RequestVersion::RFC2616),
       "HTTP/1.0" => Ok(RequestVersion::RFC1945),
-      _ => Err(Error::new(
-        ErrorKind::InvalidData,
-        format!("Unmatched http version: {}", input),
-      )),
+      _ => Err(Error::InvalidVersion(Position::default())),
+    }
+  }
+}
+
+impl std::fmt::Display for RequestVersion {
+  fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+    match self {
+      RequestVersion::RFC2616 => write!(formatter, "HTTP/1.1"),
+      RequestVersion::RFC1945 => write!(formatter, "HTTP/1.0"),
+      RequestVersion::Simple => write!(formatter, "HTTP/0.9"),
     }
   }
 }
+
 #[derive(Debug)]
 struct RequestLine {
   method: RequestMethod,
@@ -66,15 +146,110 @@ struct RequestLine {
   version: RequestVersion,
 }
 
+impl Default for RequestLine {
+  fn default() -> Self {
+    RequestLine {
+      method: RequestMethod::GET,
+      path: String::from("/"),
+      version: RequestVersion::RFC2616,
+    }
+  }
+}
+
 #[derive(Debug)]
-struct Header(pub String, pub String);
+pub(crate) struct Header(pub String, pub String);
+
+// Optional whitespace, as allowed around field values.
+pub(crate) const OWS: &[char] = &[' ', '\t'];
 
-fn parse_header_line(input: String) -> Option<Header> {
-  let mut splits = input.splitn(2, ": ");
-  if let (Some(key), Some(value)) = (splits.next(), splits.next()) {
-    return Some(Header(key.to_string(), value.to_string()));
+// Control characters other than HTAB are not allowed anywhere in a field line; a bare CR or LF
+// would be read as a line break by other parsers, which is a request smuggling vector as well.
+pub(crate) fn is_field_content(input: &str) -> bool {
+  !input.chars().any(|c| c.is_ascii_control() && c != '\t')
+}
+
+// Whether a header can be written as given, as a single line that reads back as the same field.
+pub(crate) fn is_field(name: &str, value: &str) -> bool {
+  is_token(name) && is_field_content(value)
+}
+
+// Splits a field line on its colon, trimming the value. Malformed lines (without a colon or with a
+// name that is not a token) are not headers, and are left to the caller.
+pub(crate) fn parse_header_line(input: &str) -> Result<Option<Header>, Error> {
+  let (key, value) = match input.split_once(':') {
+    Some(split) => split,
+    None => return Ok(None),
+  };
+
+  // Whitespace between the field name and colon is a known request smuggling vector and must be
+  // rejected regardless, see https://tools.ietf.org/html/rfc7230#section-3.2.4
+  if key.ends_with(OWS) {
+    return Err(Error::InvalidHeader(Position::default()));
+  }
+
+  if !is_token(key) {
+    return Ok(None);
+  }
+
+  Ok(Some(Header(key.to_string(), value.trim_matches(OWS).to_string())))
+}
+
+// Lines starting with whitespace continue the value of the previous header (obs-fold).
+fn is_obs_fold(input: &str) -> bool {
+  input.starts_with(OWS)
+}
+
+/// Implemented by the request and response heads that header lines are inserted into, so that both
+/// handle field lines through `insert_field`.
+pub(crate) trait Fields {
+  fn headers_mut(&mut self) -> &mut Headers;
+
+  fn diagnostics_mut(&mut self) -> &mut Vec<Diagnostic>;
+
+  /// Appends a complete field, enforcing the framing rules of the message.
+  fn add_header(&mut self, header: Header) -> Result<(), Error>;
+
+  /// Called once the value of the last field was extended by an obs-fold continuation line.
+  fn unfolded(&mut self, name: &str, value: &str) -> Result<(), Error>;
+}
+
+// Inserts a line following the start line, either as a field, as the continuation of the previous
+// field or as a diagnostic, depending on the options.
+pub(crate) fn insert_field<F>(
+  fields: &mut F,
+  line: String,
+  position: Position,
+  fold: ObsFold,
+  parsing: HeaderParsing,
+) -> Result<(), Error>
+where
+  F: Fields,
+{
+  if !is_field_content(&line) {
+    return Err(Error::InvalidHeader(position));
+  }
+
+  if is_obs_fold(&line) {
+    if fold == ObsFold::Reject {
+      return Err(Error::InvalidHeader(position));
+    }
+
+    let (name, value) = match fields.headers_mut().unfold(line.trim_matches(OWS)) {
+      Some((name, value)) => (name.to_string(), value.to_string()),
+      None => return Err(Error::InvalidHeader(position)),
+    };
+
+    return fields.unfolded(&name, &value).map_err(|e| e.at(position));
+  }
+
+  match (parse_header_line(&line).map_err(|e| e.at(position))?, parsing) {
+    (Some(header), _) => fields.add_header(header).map_err(|e| e.at(position)),
+    (None, HeaderParsing::Strict) => Err(Error::InvalidHeader(position)),
+    (None, HeaderParsing::Lenient) => {
+      fields.diagnostics_mut().push(Diagnostic { position, line });
+      Ok(())
+    }
   }
-  None
 }
 
 fn parse_request_line(input: String) -> Result<RequestLine, Error> {
@@ -83,6 +258,19 @@ fn parse_request_line(input: String) -> Result<RequestLine, Error> {
     (Some(first), Some(uri), Some(tail)) => {
       let version = RequestVersion::parse(tail)?;
       let method = RequestMethod::parse(first)?;
+      let target = RequestTarget::parse(uri)?;
+
+      // The authority form is reserved for (and required by) CONNECT, and the asterisk form may only
+      // be used with OPTIONS.
+      let matched = match target {
+        RequestTarget::Authority(_) => method == RequestMethod::CONNECT,
+        RequestTarget::Asterisk => method == RequestMethod::OPTIONS,
+        _ => method != RequestMethod::CONNECT,
+      };
+
+      if !matched {
+        return Err(Error::InvalidRequestTarget(Position::default()));
+      }
 
       Ok(RequestLine {
         method,
@@ -90,21 +278,70 @@ fn parse_request_line(input: String) -> Result<RequestLine, Error> {
         path: String::from(uri),
       })
     }
-    _ => Err(Error::new(
-      ErrorKind::InvalidData,
-      format!("Invalid request line: '{}'", input),
-    )),
+    _ => Err(Error::InvalidRequestLine(Position::default())),
+  }
+}
+
+// Simple requests are a `GET` followed by the request-target, without a version or headers.
+fn parse_simple_request_line(input: &str) -> Option<RequestLine> {
+  let (method, uri) = input.split_once(' ')?;
+
+  if method != "GET" || uri.contains(' ') {
+    return None;
+  }
+
+  match RequestTarget::parse(uri) {
+    Ok(RequestTarget::Origin { .. }) | Ok(RequestTarget::Absolute { .. }) => Some(RequestLine {
+      method: RequestMethod::GET,
+      path: String::from(uri),
+      version: RequestVersion::Simple,
+    }),
+    _ => None,
   }
 }
 
+pub(crate) fn is_simple_request(input: &str) -> bool {
+  parse_simple_request_line(input).is_some()
+}
+
 #[derive(Debug)]
 pub struct Builder {
   inner: Head,
+  position: Position,
+  fold: ObsFold,
+  parsing: HeaderParsing,
+}
+
+impl Default for Builder {
+  fn default() -> Self {
+    Self::new()
+  }
 }
 
 impl Builder {
   pub fn new() -> Self {
-    Builder { inner: Head::default() }
+    Builder {
+      inner: Head::default(),
+      position: Position::default(),
+      fold: ObsFold::default(),
+      parsing: HeaderParsing::default(),
+    }
+  }
+
+  pub(crate) fn with_options(options: &RecognizeOptions) -> Self {
+    Builder::new()
+      .obs_fold(options.obs_fold)
+      .header_parsing(options.header_parsing)
+  }
+
+  /// Sets how header lines continuing the previous value with leading whitespace are handled.
+  pub fn obs_fold(self, fold: ObsFold) -> Self {
+    Builder { fold, ..self }
+  }
+
+  /// Sets whether malformed header lines fail parsing or are collected as diagnostics.
+  pub fn header_parsing(self, parsing: HeaderParsing) -> Self {
+    Builder { parsing, ..self }
   }
 
   pub fn collect<D>(self) -> Head
@@ -118,30 +355,169 @@ impl Builder {
     self.inner._headers.len()
   }
 
+  pub fn is_empty(&self) -> bool {
+    self.inner._headers.is_empty()
+  }
+
+  /// Parses the line as the request line if one has not yet been inserted, otherwise as a header.
+  /// Errors are positioned relative to the lines previously inserted.
   pub fn insert(mut self, line: String) -> Result<Self, Error> {
+    let position = self.position;
+    self.position.advance(line.len());
+
     if self.inner._req.is_none() {
-      let req = parse_request_line(line)?;
+      let req = parse_request_line(line).map_err(|e| e.at(position))?;
 
       return Ok(Builder {
         inner: Head {
           _req: Some(req),
           ..self.inner
         },
+        ..self
       });
     }
 
-    if let Some(header) = parse_header_line(line) {
-      self.inner.add_header(header)?;
-      return Ok(Builder { inner: self.inner });
+    insert_field(&mut self.inner, line, position, self.fold, self.parsing)?;
+    Ok(self)
+  }
+
+  /// Replaces the method of the request line. Heads built without a request line start from
+  /// `GET / HTTP/1.1`. Fails with `Error::InvalidMethod` for extension methods that are not tokens.
+  pub fn method(mut self, method: RequestMethod) -> Result<Self, Error> {
+    if let RequestMethod::Extension(name) = &method {
+      RequestMethod::parse(name)?;
+    }
+
+    self.request_line().method = method;
+    Ok(self)
+  }
+
+  /// Replaces the request target of the request line, failing with `Error::InvalidRequestTarget`
+  /// when it is not a valid target.
+  pub fn path<S>(mut self, path: S) -> Result<Self, Error>
+  where
+    S: Into<String>,
+  {
+    let path = path.into();
+
+    if path.contains(' ') {
+      return Err(Error::InvalidRequestTarget(Position::default()));
     }
 
-    Ok(Builder { inner: self.inner })
+    RequestTarget::parse(&path)?;
+    self.request_line().path = path;
+    Ok(self)
+  }
+
+  pub fn version(mut self, version: RequestVersion) -> Self {
+    self.request_line().version = version;
+    self
+  }
+
+  /// Adds a header after any existing headers, keeping other fields of the same name. Fails with
+  /// `Error::InvalidHeader` when the name is not a token or the value holds control characters
+  /// other than HTAB.
+  pub fn header<K, V>(mut self, name: K, value: V) -> Result<Self, Error>
+  where
+    K: Into<String>,
+    V: Into<String>,
+  {
+    let (name, value) = (name.into(), value.into());
+
+    if !is_field(&name, &value) {
+      return Err(Error::InvalidHeader(Position::default()));
+    }
+
+    self.inner._headers.append(name, value);
+    self.inner.sync();
+    Ok(self)
+  }
+
+  /// Replaces every header of the given name with a single field, see [`Headers::set`]. Names and
+  /// values are checked as they are by [`header`].
+  ///
+  /// [`Headers::set`]: struct.Headers.html#method.set
+  /// [`header`]: #method.header
+  pub fn set_header<K, V>(mut self, name: K, value: V) -> Result<Self, Error>
+  where
+    K: Into<String>,
+    V: Into<String>,
+  {
+    let (name, value) = (name.into(), value.into());
+
+    if !is_field(&name, &value) {
+      return Err(Error::InvalidHeader(Position::default()));
+    }
+
+    self.inner._headers.set(name, value);
+    self.inner.sync();
+    Ok(self)
+  }
+
+  pub fn remove_header(mut self, name: &str) -> Self {
+    self.inner._headers.remove(name);
+    self.inner.sync();
+    self
+  }
+
+  // Validates what could not be checked line by line, once the head is complete.
+  pub(crate) fn finish(&self) -> Result<(), Error> {
+    self.inner.check_framing().map_err(|e| e.at(self.position))
+  }
+
+  // Inserts the line as the request line of an HTTP/0.9 simple request, if it is one.
+  pub(crate) fn insert_simple(&mut self, line: &str) -> bool {
+    match parse_simple_request_line(line) {
+      Some(req) if self.inner._req.is_none() => {
+        self.position.advance(line.len());
+        self.inner._req = Some(req);
+        true
+      }
+      _ => false,
+    }
+  }
+
+  fn request_line(&mut self) -> &mut RequestLine {
+    self.inner._req.get_or_insert_with(RequestLine::default)
+  }
+}
+
+/// Starts a builder from a parsed head, e.g. to rewrite a request before forwarding it:
+///
+/// ```rust
+/// use async_std::task::block_on;
+/// use elaine::{recognize, Builder, Head};
+///
+/// block_on(async {
+///   let mut req: &[u8] = b"GET /api/things HTTP/1.1\r\nHost: example.com\r\n\r\n";
+///   let head = recognize(&mut req).await?;
+///   let forwarded = Builder::from(head)
+///     .path("/things")?
+///     .header("Via", "1.1 elaine")?
+///     .collect::<Head>();
+///   assert_eq!(
+///     forwarded.to_bytes(),
+///     b"GET /things HTTP/1.1\r\nHost: example.com\r\nVia: 1.1 elaine\r\n\r\n".to_vec()
+///   );
+///   Ok::<(), elaine::Error>(())
+/// })
+/// .unwrap();
+/// ```
+impl From<Head> for Builder {
+  fn from(head: Head) -> Builder {
+    Builder {
+      inner: head,
+      position: Position::default(),
+      fold: ObsFold::default(),
+      parsing: HeaderParsing::default(),
+    }
   }
 }
 
 #[derive(Debug, Default)]
 pub struct Head {
-  _headers: Vec<Header>,
+  _headers: Headers,
+  _diagnostics: Vec<Diagnostic>,
   _req: Option<RequestLine>,
   _len: Option<usize>,
   _auth: Option<String>,
@@ -153,22 +529,21 @@ impl From<Builder> for Head {
   }
 }
 
-fn take_equal<S: std::fmt::Display>(key: &String, attempt: &S) -> Option<String> {
-  let lower_key = key.to_lowercase();
-  let lower_value = format!("{}", attempt).to_lowercase();
-
-  if lower_key.as_str() == lower_value.as_str() {
-    return Some(lower_key);
-  }
-
-  None
-}
-
+#[allow(clippy::len_without_is_empty)]
 impl Head {
   pub fn path(&self) -> Option<String> {
     self._req.as_ref().map(|r| r.path.clone())
   }
 
+  /// The request-target of the request line, parsed into one of its four forms. Returns `None`
+  /// when there is no request line, or when a path set through the [`Builder`] is not a valid
+  /// request-target.
+  ///
+  /// [`Builder`]: struct.Builder.html
+  pub fn target(&self) -> Option<RequestTarget> {
+    self._req.as_ref().and_then(|r| RequestTarget::parse(&r.path).ok())
+  }
+
   pub fn version(&self) -> Option<RequestVersion> {
     self._req.as_ref().map(|r| r.version)
   }
@@ -185,49 +560,318 @@ impl Head {
   where
     S: std::fmt::Display,
   {
-    self
+    self._headers.get(&target.to_string()).map(String::from)
+  }
+
+  /// How the body following the head is framed. Heads read by [`recognize`] never have
+  /// `CloseDelimited` bodies; requests whose transfer codings do not end in chunked are rejected.
+  ///
+  /// [`recognize`]: fn.recognize.html
+  pub fn framing(&self) -> BodyFraming {
+    match (chunked(&self._headers), self._len) {
+      (Some(true), _) => BodyFraming::Chunked,
+      (Some(false), _) => BodyFraming::CloseDelimited,
+      (None, Some(len)) => BodyFraming::Length(len),
+      (None, None) => BodyFraming::None,
+    }
+  }
+
+  /// The credentials of the `Authorization` header, or `None` when it is missing or malformed.
+  pub fn credentials(&self) -> Option<Credentials> {
+    self._auth.as_deref().and_then(Credentials::parse)
+  }
+
+  /// The credentials of the `Proxy-Authorization` header, or `None` when it is missing or
+  /// malformed.
+  pub fn proxy_credentials(&self) -> Option<Credentials> {
+    self._headers.get(PROXY_AUTHORIZATION).and_then(Credentials::parse)
+  }
+
+  /// The `Host` header, or `None` when there is none. Fails with an `Error::InvalidHeaderValue`
+  /// when the value is malformed or the header is repeated.
+  pub fn host(&self) -> Result<Option<Host>, Error> {
+    self.single(HOST)?.map(Host::parse).transpose()
+  }
+
+  /// The `Content-Type` header, or `None` when there is none. Fails with an
+  /// `Error::InvalidHeaderValue` when the value is malformed or the header is repeated.
+  pub fn content_type(&self) -> Result<Option<MediaType>, Error> {
+    self.single(CONTENT_TYPE)?.map(MediaType::parse).transpose()
+  }
+
+  /// The lowercased connection options of every `Connection` header, in order. Fails with an
+  /// `Error::InvalidHeaderValue` when an option is not a token.
+  pub fn connection(&self) -> Result<Vec<String>, Error> {
+    connection_options(&self._headers)
+  }
+
+  pub fn user_agent(&self) -> Option<&str> {
+    self._headers.get(USER_AGENT)
+  }
+
+  pub fn referer(&self) -> Option<&str> {
+    self._headers.get(REFERER)
+  }
+
+  pub fn origin(&self) -> Option<&str> {
+    self._headers.get(ORIGIN)
+  }
+
+  /// The cookies of every `Cookie` header, in order; see [`Cookies`].
+  ///
+  /// [`Cookies`]: struct.Cookies.html
+  pub fn cookies(&self) -> Cookies<'_> {
+    Cookies::new(self._headers.get_all(COOKIE))
+  }
+
+  /// Every header field of the head, in the order they were received.
+  pub fn headers(&self) -> &Headers {
+    &self._headers
+  }
+
+  /// The malformed header lines skipped while parsing leniently, in the order they were read.
+  pub fn diagnostics(&self) -> &[Diagnostic] {
+    &self._diagnostics
+  }
+
+  /// The head in wire format; the request line followed by every header in its original order and
+  /// the empty line terminating the head.
+  pub fn to_bytes(&self) -> Vec<u8> {
+    self.to_string().into_bytes()
+  }
+
+  /// Writes the head, as returned by [`to_bytes`], to the writer.
+  ///
+  /// [`to_bytes`]: #method.to_bytes
+  #[cfg(feature = "futures-io")]
+  pub async fn write_to<W>(&self, writer: W) -> Result<(), Error>
+  where
+    W: futures_io::AsyncWrite + Unpin,
+  {
+    crate::recognize::write_all(writer, &self.to_bytes()).await
+  }
+
+  // The value of a header that may only be sent once.
+  pub(crate) fn single(&self, name: &'static str) -> Result<Option<&str>, Error> {
+    let mut values = self._headers.get_all(name);
+
+    match (values.next(), values.next()) {
+      (value, None) => Ok(value),
+      _ => Err(Error::InvalidHeaderValue(name)),
+    }
+  }
+
+  // Keeps the parsed content length and credentials consistent with headers modified through the
+  // builder.
+  fn sync(&mut self) {
+    self._len = self
       ._headers
-      .iter()
-      .filter_map(|Header(key, value)| take_equal(key, &target).map(|_| value.clone()))
-      .nth(0)
+      .get(CONTENT_LENGTH)
+      .and_then(|value| parse_content_length(value).ok());
+    self.sync_auth();
+  }
+
+  fn sync_auth(&mut self) {
+    self._auth = self._headers.get(AUTHORIZATION).map(String::from);
+  }
+
+  // Transfer codings can only be checked once every header is known, as they may be spread over
+  // several fields.
+  fn check_framing(&self) -> Result<(), Error> {
+    let chunks = codings(&self._headers).filter(|coding| coding == "chunked").count();
+
+    match chunked(&self._headers) {
+      Some(false) => Err(Error::InvalidFraming(Position::default())),
+      Some(true) if chunks > 1 => Err(Error::InvalidFraming(Position::default())),
+      _ => Ok(()),
+    }
+  }
+}
+
+impl Fields for Head {
+  fn headers_mut(&mut self) -> &mut Headers {
+    &mut self._headers
   }
 
+  fn diagnostics_mut(&mut self) -> &mut Vec<Diagnostic> {
+    &mut self._diagnostics
+  }
+
+  // Enforces the message length rules of RFC 7230 §3.3.3 as headers arrive; conflicting lengths, or
+  // a request framed by both a length and a transfer coding, are rejected to prevent smuggling.
   fn add_header(&mut self, header: Header) -> Result<(), Error> {
     let Header(key, value) = header;
 
-    if key == "Content-Length" {
-      match value.parse::<usize>() {
-        Ok(value) => self._len = Some(value),
-        Err(e) => {
-          return Err(Error::new(
-            ErrorKind::InvalidData,
-            format!("Invalid content length ('{}'): {:?}", value, e),
-          ))
-        }
+    if key.eq_ignore_ascii_case(CONTENT_LENGTH) {
+      let len = parse_content_length(&value)?;
+
+      if self._len.is_some_and(|previous| previous != len) {
+        return Err(Error::InvalidContentLength(Position::default()));
+      }
+
+      if self._headers.contains(TRANSFER_ENCODING) {
+        return Err(Error::InvalidFraming(Position::default()));
+      }
+
+      self._len = Some(len);
+    }
+
+    if key.eq_ignore_ascii_case(TRANSFER_ENCODING) {
+      // Nothing may follow the chunked coding, which is what marks the end of the body.
+      if self._len.is_some() || chunked(&self._headers) == Some(true) {
+        return Err(Error::InvalidFraming(Position::default()));
       }
     }
 
-    self._headers.push(Header(key, value));
+    let auth = key.eq_ignore_ascii_case(AUTHORIZATION);
+    self._headers.append(key, value);
+
+    if auth && self._auth.is_none() {
+      self.sync_auth();
+    }
+
+    Ok(())
+  }
+
+  fn unfolded(&mut self, name: &str, value: &str) -> Result<(), Error> {
+    if name.eq_ignore_ascii_case(CONTENT_LENGTH) {
+      self._len = Some(parse_content_length(value)?);
+    }
+
+    if name.eq_ignore_ascii_case(AUTHORIZATION) {
+      self.sync_auth();
+    }
+
     Ok(())
   }
 }
 
 impl std::fmt::Display for Head {
   fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
-    write!(
-      formatter,
-      "{:?} {} {:?}\r\nContent-Length: {:?}\r\n\r\n",
-      self.method(),
-      self.path().unwrap_or(String::from("<UNKNOWN>")),
-      self.version(),
-      self._len,
-    )
+    match &self._req {
+      Some(req) if req.version == RequestVersion::Simple => {
+        return write!(formatter, "{} {}\r\n", req.method, req.path);
+      }
+      Some(req) => write!(formatter, "{} {} {}\r\n", req.method, req.path, req.version)?,
+      None => {}
+    }
+
+    for (name, value) in &self._headers {
+      write!(formatter, "{}: {}\r\n", name, value)?;
+    }
+
+    write!(formatter, "\r\n")
+  }
+}
+
+/// The lowercased connection options of every `Connection` field in the headers, shared by request
+/// heads and the responses written by the server.
+pub(crate) fn connection_options(headers: &Headers) -> Result<Vec<String>, Error> {
+  let mut options = Vec::new();
+
+  for option in headers.get_all(CONNECTION).flat_map(|value| value.split(',')) {
+    match option.trim_matches(OWS) {
+      "" => continue,
+      option if is_token(option) => options.push(option.to_ascii_lowercase()),
+      _ => return Err(Error::InvalidHeaderValue(CONNECTION)),
+    }
   }
+
+  Ok(options)
 }
 
 #[cfg(test)]
 mod test {
-  use super::{Builder, Head};
+  use super::{parse_header_line, Builder, Head, Header, RequestMethod};
+
+  #[test]
+  fn test_method_extension() {
+    assert_eq!(
+      RequestMethod::parse("PROPFIND").unwrap(),
+      RequestMethod::Extension("PROPFIND".to_string())
+    );
+    assert!(RequestMethod::parse("GE(T").is_err());
+    assert!(RequestMethod::parse("").is_err());
+  }
+
+  #[test]
+  fn test_method_semantics() {
+    assert!(RequestMethod::GET.is_safe());
+    assert!(!RequestMethod::PUT.is_safe());
+    assert!(RequestMethod::PUT.is_idempotent());
+    assert!(!RequestMethod::POST.is_idempotent());
+    assert!(!RequestMethod::Extension("PURGE".to_string()).is_idempotent());
+  }
+
+  #[test]
+  fn test_display_wire_format() {
+    let head = Builder::new()
+      .insert("PURGE /cache HTTP/1.0".to_string())
+      .unwrap()
+      .insert("Host: example.com".to_string())
+      .unwrap()
+      .insert("X-Thing: a".to_string())
+      .unwrap()
+      .collect::<Head>();
+    assert_eq!(
+      head.to_string(),
+      "PURGE /cache HTTP/1.0\r\nHost: example.com\r\nX-Thing: a\r\n\r\n"
+    );
+  }
+
+  #[test]
+  fn test_builder_rewrite() {
+    let head = Builder::new()
+      .insert("POST /upload HTTP/1.1".to_string())
+      .unwrap()
+      .insert("Content-Length: 10".to_string())
+      .unwrap()
+      .collect::<Head>();
+    let head = Builder::from(head)
+      .method(RequestMethod::PUT)
+      .and_then(|builder| builder.set_header("content-length", "4"))
+      .unwrap()
+      .collect::<Head>();
+    assert_eq!(head.method(), Some(RequestMethod::PUT));
+    assert_eq!(head.len(), Some(4));
+    assert_eq!(head.to_string(), "PUT /upload HTTP/1.1\r\nContent-Length: 4\r\n\r\n");
+  }
+
+  #[test]
+  fn test_builder_rejects_injection() {
+    let builder = || Builder::new().method(RequestMethod::GET).unwrap();
+    let extension = RequestMethod::Extension("GET / HTTP/1.1\r\n\r\nGET".to_string());
+    assert!(Builder::new().method(extension).is_err());
+    assert!(builder().path("/a HTTP/1.1\r\nX-Injected: 1\r\n\r\nGET /b").is_err());
+    assert!(builder().path("/a b").is_err());
+    assert!(builder().path("").is_err());
+    assert!(builder().header("X", "a\r\nX-Injected: 1").is_err());
+    assert!(builder().header("X", "a\nb").is_err());
+    assert!(builder().header("X", "a\0b").is_err());
+    assert!(builder().header("X: a\r\nY", "b").is_err());
+    assert!(builder().header("", "b").is_err());
+    assert!(builder().set_header("X", "a\rb").is_err());
+    assert!(builder().set_header("X Y", "b").is_err());
+
+    let head = builder()
+      .path("/search?q=a%20b")
+      .and_then(|builder| builder.header("X", "a\tb"))
+      .unwrap()
+      .collect::<Head>();
+    assert_eq!(head.to_string(), "GET /search?q=a%20b HTTP/1.1\r\nX: a\tb\r\n\r\n");
+  }
+
+  #[test]
+  fn test_header_whitespace() {
+    let Header(key, value) = parse_header_line("Key:value").unwrap().unwrap();
+    assert_eq!((key.as_str(), value.as_str()), ("Key", "value"));
+    let Header(key, value) = parse_header_line("Key: \t spaced out \t").unwrap().unwrap();
+    assert_eq!((key.as_str(), value.as_str()), ("Key", "spaced out"));
+    let Header(_, value) = parse_header_line("Empty:").unwrap().unwrap();
+    assert_eq!(value, "");
+    assert!(parse_header_line("Key : value").is_err());
+    assert!(parse_header_line("Key\t: value").is_err());
+  }
 
   #[test]
   fn test_find_header_none() {
@@ -0,0 +1,212 @@
+/// The header fields of a message, in the order they were received. Field names keep their
+/// original casing but are compared case-insensitively, and a name may appear any number of times
+/// (as is common with `Set-Cookie`, `Via`, `Forwarded` and `Accept`).
+///
+/// ```rust
+/// use async_std::task::block_on;
+/// use elaine::recognize;
+///
+/// block_on(async {
+///   let mut req: &[u8] = b"GET / HTTP/1.1\r\nVia: 1.0 fred\r\nHost: example.com\r\nvia: 1.1 nowhere.com\r\n\r\n";
+///   let head = recognize(&mut req).await.unwrap();
+///   let headers = head.headers();
+///   assert_eq!(headers.len(), 3);
+///   assert_eq!(headers.get("VIA"), Some("1.0 fred"));
+///   assert_eq!(headers.get_all("via").collect::<Vec<_>>(), vec!["1.0 fred", "1.1 nowhere.com"]);
+///   assert_eq!(headers.combined("via"), Some("1.0 fred, 1.1 nowhere.com".to_string()));
+/// });
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Headers {
+  entries: Vec<(String, String)>,
+}
+
+impl Headers {
+  pub fn new() -> Self {
+    Headers::default()
+  }
+
+  /// The number of fields, counting each occurrence of a repeated name.
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  /// The value of the first field with the given name.
+  pub fn get(&self, name: &str) -> Option<&str> {
+    self
+      .entries
+      .iter()
+      .find(|(key, _)| key.eq_ignore_ascii_case(name))
+      .map(|(_, value)| value.as_str())
+  }
+
+  /// The values of every field with the given name, in the order they were received.
+  pub fn get_all<'a>(&'a self, name: &'a str) -> GetAll<'a> {
+    GetAll {
+      inner: self.entries.iter(),
+      name,
+    }
+  }
+
+  /// Combines the values of every field with the given name into a single comma-separated value,
+  /// as described in [RFC 7230 §3.2.2][combine]. Note that `Set-Cookie` values may contain commas
+  /// and should be read with [`get_all`] instead.
+  ///
+  /// [combine]: https://tools.ietf.org/html/rfc7230#section-3.2.2
+  /// [`get_all`]: #method.get_all
+  pub fn combined(&self, name: &str) -> Option<String> {
+    let values = self.get_all(name).collect::<Vec<&str>>();
+
+    if values.is_empty() {
+      return None;
+    }
+
+    Some(values.join(", "))
+  }
+
+  pub fn contains(&self, name: &str) -> bool {
+    self.get(name).is_some()
+  }
+
+  /// Iterates over every field as a `(name, value)` pair, in the order they were received.
+  pub fn iter(&self) -> HeaderIter<'_> {
+    HeaderIter {
+      inner: self.entries.iter(),
+    }
+  }
+
+  /// Adds a field after all existing fields, regardless of whether its name is already present.
+  pub fn append<K, V>(&mut self, name: K, value: V)
+  where
+    K: Into<String>,
+    V: Into<String>,
+  {
+    self.entries.push((name.into(), value.into()));
+  }
+
+  /// Replaces the value of the first field with the given name, removing any others; the field is
+  /// appended if the name is not yet present.
+  pub fn set<K, V>(&mut self, name: K, value: V)
+  where
+    K: Into<String>,
+    V: Into<String>,
+  {
+    let name = name.into();
+    let value = value.into();
+
+    match self.entries.iter().position(|(key, _)| key.eq_ignore_ascii_case(&name)) {
+      Some(index) => {
+        self.entries[index].1 = value;
+        let rest = self.entries.split_off(index + 1);
+        let rest = rest.into_iter().filter(|(key, _)| !key.eq_ignore_ascii_case(&name));
+        self.entries.extend(rest);
+      }
+      None => self.entries.push((name, value)),
+    }
+  }
+
+  // Appends an obs-fold continuation to the value of the last field, separated by a single space.
+  pub(crate) fn unfold(&mut self, continuation: &str) -> Option<(&str, &str)> {
+    let (name, value) = self.entries.last_mut()?;
+
+    if !continuation.is_empty() {
+      value.push(' ');
+      value.push_str(continuation);
+    }
+
+    Some((name.as_str(), value.as_str()))
+  }
+
+  /// Removes every field with the given name, returning true if any were present.
+  pub fn remove(&mut self, name: &str) -> bool {
+    let before = self.entries.len();
+    self.entries.retain(|(key, _)| !key.eq_ignore_ascii_case(name));
+    self.entries.len() != before
+  }
+}
+
+impl<'a> IntoIterator for &'a Headers {
+  type Item = (&'a str, &'a str);
+  type IntoIter = HeaderIter<'a>;
+
+  fn into_iter(self) -> HeaderIter<'a> {
+    self.iter()
+  }
+}
+
+/// An iterator over the fields of [`Headers`].
+///
+/// [`Headers`]: struct.Headers.html
+#[derive(Debug, Clone)]
+pub struct HeaderIter<'a> {
+  inner: std::slice::Iter<'a, (String, String)>,
+}
+
+impl<'a> Iterator for HeaderIter<'a> {
+  type Item = (&'a str, &'a str);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.inner.next().map(|(name, value)| (name.as_str(), value.as_str()))
+  }
+}
+
+/// An iterator over the values of every field with a given name, returned by [`Headers::get_all`].
+///
+/// [`Headers::get_all`]: struct.Headers.html#method.get_all
+#[derive(Debug, Clone)]
+pub struct GetAll<'a> {
+  inner: std::slice::Iter<'a, (String, String)>,
+  name: &'a str,
+}
+
+impl<'a> Iterator for GetAll<'a> {
+  type Item = &'a str;
+
+  fn next(&mut self) -> Option<&'a str> {
+    let name = self.name;
+    self
+      .inner
+      .find(|(key, _)| key.eq_ignore_ascii_case(name))
+      .map(|(_, value)| value.as_str())
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::Headers;
+
+  #[test]
+  fn test_order_and_casing() {
+    let mut headers = Headers::new();
+    headers.append("Set-Cookie", "a=1");
+    headers.append("Host", "example.com");
+    headers.append("set-cookie", "b=2; Expires=Wed, 21 Oct 2015 07:28:00 GMT");
+
+    let names = headers.iter().map(|(name, _)| name).collect::<Vec<&str>>();
+    assert_eq!(names, vec!["Set-Cookie", "Host", "set-cookie"]);
+    assert_eq!(headers.get_all("SET-COOKIE").count(), 2);
+    assert!(headers.contains("host"));
+    assert!(!headers.contains("accept"));
+    assert_eq!(headers.combined("accept"), None);
+  }
+
+  #[test]
+  fn test_set_and_remove() {
+    let mut headers = Headers::new();
+    headers.append("Via", "1.0 fred");
+    headers.append("Host", "example.com");
+    headers.append("via", "1.1 nowhere.com");
+
+    headers.set("VIA", "1.1 elaine");
+    let fields = headers.iter().collect::<Vec<(&str, &str)>>();
+    assert_eq!(fields, vec![("Via", "1.1 elaine"), ("Host", "example.com")]);
+
+    assert!(headers.remove("host"));
+    assert!(!headers.remove("host"));
+    assert_eq!(headers.len(), 1);
+  }
+}
@@ -1,5 +1,20 @@
 //! The primary export of this crate is [`recognize`], a lightweight and potentially incomplete http head parser
-//! implementation for readers that satisfy the [`async_std::io::Read`][read] trait.
+//! implementation for readers that satisfy the [`futures_io::AsyncRead`][read] trait (which includes every
+//! [`async_std::io::Read`][async-std-read]). Its counterpart, [`recognize_response`], reads the head of an http
+//! response with the same guarantees, and [`Connection`] reads the requests of a keep-alive connection one
+//! after another.
+//!
+//! ## Features
+//!
+//! - `async-std` (default) - the async parsing functions, for `async-std` readers, and
+//!   [`recognize_with_timeout`](fn.recognize_with_timeout.html).
+//! - `futures-io` - the same async parsing functions, without depending on `async-std`.
+//! - `tokio` - the [`tokio`](tokio/index.html) module, for readers implementing `tokio::io::AsyncRead`.
+//! - `client` - the [`client`](client/index.html) module, for sending requests over `async-std` streams.
+//! - `server` - the [`server`](server/index.html) module, a minimal HTTP/1.1 server on top of `async-std`.
+//!
+//! The [`blocking`](blocking/index.html) module and the sans-I/O [`Parser`](struct.Parser.html) are always
+//! available, and the [`websocket`](websocket/index.html) module is available along with the async functions.
 //!
 //! ## Example
 //!
@@ -23,11 +38,85 @@
 //! ```
 //!
 //! [`recognize`]: fn.recognize.html
-//! [read]: https://docs.rs/async-std/0.99.12/async_std/io/trait.Read.html
-extern crate async_std;
+//! [`recognize_response`]: fn.recognize_response.html
+//! [`Connection`]: struct.Connection.html
+//! [read]: https://docs.rs/futures-io/latest/futures_io/trait.AsyncRead.html
+//! [async-std-read]: https://docs.rs/async-std/latest/async_std/io/trait.Read.html
+mod base64;
+
+pub mod blocking;
+
+#[cfg(feature = "futures-io")]
+mod body;
+#[cfg(feature = "futures-io")]
+pub use body::Body;
+
+#[cfg(feature = "futures-io")]
+mod buffered;
+#[cfg(feature = "futures-io")]
+pub use buffered::{recognize_buffered, recognize_buffered_with};
+
+#[cfg(feature = "client")]
+pub mod client;
+
+#[cfg(feature = "futures-io")]
+mod connection;
+#[cfg(feature = "futures-io")]
+pub use connection::Connection;
+
+mod cookie;
+pub use cookie::{Cookies, SameSite, SetCookie};
+
+mod credentials;
+pub use credentials::Credentials;
+
+mod error;
+pub use error::{Diagnostic, Error, Position, TimedOut};
+
+mod framing;
+pub use framing::BodyFraming;
 
 mod head;
-pub use head::{Builder, Head, RequestMethod, RequestVersion};
+pub use head::{Builder, Head, RequestMethod, RequestVersion, HTTP2_PREFACE};
 
+mod headers;
+pub use headers::{GetAll, HeaderIter, Headers};
+
+mod machine;
+
+mod options;
+pub use options::{HeaderParsing, LimitExceeded, Limits, ObsFold, RecognizeOptions, Timeout, Timeouts};
+
+mod parser;
+pub use parser::{Parser, ResponseParser, Status};
+
+mod response;
+pub use response::{ResponseBuilder, ResponseHead};
+
+mod target;
+pub use target::RequestTarget;
+
+#[cfg(feature = "futures-io")]
 mod recognize;
-pub use recognize::recognize;
+#[cfg(feature = "futures-io")]
+pub use recognize::{recognize, recognize_response, recognize_response_with, recognize_with};
+
+#[cfg(feature = "server")]
+pub mod server;
+
+#[cfg(feature = "async-std")]
+mod timeout;
+#[cfg(feature = "async-std")]
+pub use timeout::{recognize_response_with_timeout, recognize_with_timeout};
+
+#[cfg(feature = "tokio")]
+pub mod tokio;
+
+#[cfg(feature = "futures-io")]
+mod sha1;
+
+mod values;
+pub use values::{Host, MediaType};
+
+#[cfg(feature = "futures-io")]
+pub mod websocket;
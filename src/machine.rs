@@ -0,0 +1,578 @@
+use crate::error::{Error, Position};
+use crate::head::{is_simple_request, Builder, PREFACE, PREFACE_LINE};
+use crate::options::{LimitExceeded, Limits, RecognizeOptions};
+use crate::response::ResponseBuilder;
+
+/// Implemented by the builders that accumulate the lines read off of a reader; the first line
+/// inserted is expected to be the start line (request or status) and each subsequent one a header.
+pub(crate) trait Insert {
+  fn insert_line(&mut self, line: String) -> Result<(), Error>;
+
+  /// Inserts a start line that ends the head by itself (an HTTP/0.9 simple request), returning false
+  /// without inserting anything for every other line.
+  fn insert_simple(&mut self, _line: &str) -> bool {
+    false
+  }
+
+  /// Whether the start line is the first line of the HTTP/2 connection preface.
+  fn is_preface(&self, _line: &str) -> bool {
+    false
+  }
+
+  /// Called once every line of the head was inserted.
+  fn finish(&mut self) -> Result<(), Error> {
+    Ok(())
+  }
+}
+
+impl Insert for Builder {
+  fn insert_line(&mut self, line: String) -> Result<(), Error> {
+    *self = std::mem::take(self).insert(line)?;
+    Ok(())
+  }
+
+  fn insert_simple(&mut self, line: &str) -> bool {
+    Builder::insert_simple(self, line)
+  }
+
+  fn is_preface(&self, line: &str) -> bool {
+    line == PREFACE_LINE
+  }
+
+  fn finish(&mut self) -> Result<(), Error> {
+    Builder::finish(self)
+  }
+}
+
+impl Insert for ResponseBuilder {
+  fn insert_line(&mut self, line: String) -> Result<(), Error> {
+    *self = std::mem::take(self).insert(line)?;
+    Ok(())
+  }
+}
+
+fn invalid_utf8() -> Error {
+  Error::InvalidUtf8(Position::default())
+}
+
+/// Returns the number of bytes still required to complete a utf-8 character that was split by the
+/// end of the input, failing for input that can never be valid.
+pub(crate) fn utf8_debt(input: &[u8]) -> Result<usize, Error> {
+  match std::str::from_utf8(input) {
+    Ok(_) => Ok(0),
+    Err(e) if e.error_len().is_some() => Err(invalid_utf8()),
+    Err(e) => {
+      let partial = &input[e.valid_up_to()..];
+      let width = match partial[0] {
+        0xC0..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF7 => 4,
+        _ => return Err(invalid_utf8()),
+      };
+      Ok(width - partial.len())
+    }
+  }
+}
+
+pub(crate) fn decode(input: &[u8]) -> Result<&str, Error> {
+  std::str::from_utf8(input).map_err(|_| invalid_utf8())
+}
+
+// Tracks the amount of data buffered so far against the configured limits.
+#[derive(Debug)]
+pub(crate) struct Budget {
+  limits: Limits,
+  lines: usize,
+  offset: usize,
+  bytes: usize,
+}
+
+impl Budget {
+  pub(crate) fn new(limits: &Limits) -> Self {
+    Budget {
+      limits: *limits,
+      lines: 0,
+      offset: 0,
+      bytes: 0,
+    }
+  }
+
+  // The position of the line currently being read.
+  pub(crate) fn position(&self) -> Position {
+    Position {
+      offset: self.offset,
+      line: self.lines + 1,
+    }
+  }
+
+  pub(crate) fn exceeded(&self, limit: LimitExceeded) -> Error {
+    Error::LimitExceeded(limit, self.position())
+  }
+
+  pub(crate) fn read(&mut self, amount: usize) -> Result<(), Error> {
+    self.bytes += amount;
+    if self.bytes > self.limits.head {
+      return Err(self.exceeded(LimitExceeded::HeadSize));
+    }
+    Ok(())
+  }
+
+  pub(crate) fn line(&mut self, length: usize) -> Result<(), Error> {
+    // The first line is the request (or status) line, every other one is a header.
+    if self.lines + 1 > self.limits.header_count + 1 {
+      return Err(self.exceeded(LimitExceeded::HeaderCount));
+    }
+    self.lines += 1;
+    self.offset += length + 2;
+    Ok(())
+  }
+
+  pub(crate) fn current(&self, length: usize) -> Result<(), Error> {
+    match self.lines {
+      0 if length > self.limits.request_line => Err(self.exceeded(LimitExceeded::RequestLine)),
+      0 => Ok(()),
+      _ if length > self.limits.header_line => Err(self.exceeded(LimitExceeded::HeaderLine)),
+      _ => Ok(()),
+    }
+  }
+}
+
+pub(crate) fn invalid_read<H>(budget: &Budget) -> Result<H, Error> {
+  Err(Error::UnexpectedEof(Position {
+    offset: budget.bytes,
+    ..budget.position()
+  }))
+}
+
+/// The state machine driving `recognize` and its variants; it is fed chunks of (complete) utf-8
+/// characters and tells the driver how many bytes may be read next without reading past the end of
+/// the head. The terminator is matched on bytes, the same way [`Terminator`] does for buffered
+/// readers, so a CR is only part of a line break when a LF follows it.
+///
+/// [`Terminator`]: struct.Terminator.html
+#[derive(Debug)]
+pub(crate) struct Machine<B> {
+  // How much of the CR LF CR LF terminator ends the bytes read so far.
+  matched: usize,
+  // The bytes of the line being read.
+  line: Vec<u8>,
+  // The last complete line, which is inserted once the next one starts; the start line of the
+  // HTTP/2 preface can only be told apart once the empty line following it was read.
+  last: Option<String>,
+  budget: Budget,
+  builder: B,
+  // The start line while it is read in single characters, when simple requests are accepted.
+  first: Option<String>,
+  // The remainder of the HTTP/2 preface, once its first line has been read.
+  preface: Option<&'static str>,
+}
+
+impl<B> Machine<B>
+where
+  B: Insert,
+{
+  pub(crate) fn new(builder: B, options: &RecognizeOptions) -> Self {
+    Machine {
+      matched: 0,
+      line: Vec::new(),
+      last: None,
+      budget: Budget::new(&options.limits),
+      builder,
+      first: if options.http09 { Some(String::new()) } else { None },
+      preface: None,
+    }
+  }
+
+  /// The number of bytes the next read should request.
+  pub(crate) fn capacity(&self) -> usize {
+    if let Some(rest) = self.preface {
+      return rest.len();
+    }
+
+    // Reading pairs of characters never reaches past a CR LF, unless its CR has already been read.
+    if let Some(line) = &self.first {
+      return if line.ends_with('\r') { 1 } else { 2 };
+    }
+
+    // The head ends no sooner than after the rest of the terminator.
+    TERMINATOR.len() - self.matched
+  }
+
+  /// The position errors raised by the current read should be reported at.
+  pub(crate) fn position(&self) -> Position {
+    Position {
+      offset: self.budget.bytes,
+      ..self.budget.position()
+    }
+  }
+
+  /// Handles a chunk of characters, returning true once the terminating CR LF sequence was read.
+  pub(crate) fn advance(&mut self, chunk: &str) -> Result<bool, Error> {
+    self.budget.read(chunk.len())?;
+
+    if chunk.is_empty() {
+      return invalid_read(&self.budget);
+    }
+
+    if let Some(rest) = self.preface.take() {
+      return match rest.strip_prefix(chunk) {
+        Some("") => Err(Error::Http2Preface),
+        Some(rest) => {
+          self.preface = Some(rest);
+          Ok(false)
+        }
+        None => Err(Error::InvalidRequestLine(Position::default())),
+      };
+    }
+
+    if let Some(line) = self.first.as_mut() {
+      line.push_str(chunk);
+
+      if !line.ends_with("\r\n") {
+        return self.budget.current(line.trim_end_matches('\r').len()).map(|_| false);
+      }
+
+      let mut line = self.first.take().unwrap_or_default();
+      line.truncate(line.len() - 2);
+
+      // Empty lines preceding the start line are ignored.
+      if line.is_empty() {
+        self.first = Some(line);
+        return Ok(false);
+      }
+
+      return self.start(line);
+    }
+
+    for byte in chunk.bytes() {
+      self.matched = match byte {
+        b if b == TERMINATOR[self.matched] => self.matched + 1,
+        b'\r' => 1,
+        _ => 0,
+      };
+
+      if self.matched == TERMINATOR.len() {
+        return self.terminate();
+      }
+
+      if byte == b'\n' && self.line.last() == Some(&b'\r') {
+        self.line.pop();
+        let line = String::from_utf8(std::mem::take(&mut self.line)).map_err(|_| invalid_utf8())?;
+        self.budget.current(line.len())?;
+
+        // Empty lines preceding the start line are ignored.
+        if !line.is_empty() || self.last.is_some() || self.budget.lines > 0 {
+          self.last = Some(line);
+        }
+        continue;
+      }
+
+      self.line.push(byte);
+
+      // A CR following a line break may still be the start of the terminator.
+      if self.matched != 3 {
+        if let Some(complete) = self.last.take() {
+          self.insert(complete)?;
+        }
+      }
+    }
+
+    self.budget.current(self.line.len())?;
+    Ok(false)
+  }
+
+  fn insert(&mut self, line: String) -> Result<(), Error> {
+    self.budget.line(line.len())?;
+    self.builder.insert_line(line)
+  }
+
+  // Handles a start line read on its own, which may be a simple request or the HTTP/2 preface.
+  fn start(&mut self, line: String) -> Result<bool, Error> {
+    if self.builder.is_preface(&line) {
+      self.preface = Some(&PREFACE[line.len() + 2..]);
+      return Ok(false);
+    }
+
+    self.budget.line(line.len())?;
+
+    if self.builder.insert_simple(&line) {
+      return Ok(true);
+    }
+
+    // Having just read a CR LF, the next two bytes either terminate the head or start a header.
+    self.builder.insert_line(line)?;
+    self.matched = 2;
+    Ok(false)
+  }
+
+  // The HTTP/2 preface reads like a head with only a start line, until its final `SM` line. Empty
+  // lines read before any start line do not end the head, they are skipped like any other.
+  fn terminate(&mut self) -> Result<bool, Error> {
+    match self.last.as_deref() {
+      None if self.budget.lines == 0 => {
+        self.line.clear();
+        self.matched = 2;
+        Ok(false)
+      }
+      Some(line) if self.budget.lines == 0 && self.builder.is_preface(line) => {
+        self.preface = Some(&PREFACE[line.len() + 4..]);
+        self.last = None;
+        self.line.clear();
+        self.matched = 0;
+        Ok(false)
+      }
+      _ => Ok(true),
+    }
+  }
+
+  pub(crate) fn finish(mut self) -> Result<B, Error> {
+    if let Some(last) = self.last.take() {
+      self.insert(last)?;
+    }
+
+    self.builder.finish()?;
+    Ok(self.builder)
+  }
+}
+
+/// Drives a `Machine` with bytes as they arrive, gathering them into the chunks it asks for. Nothing
+/// past the end of the head is ever taken from the input.
+#[derive(Debug)]
+pub(crate) struct Feeder<B> {
+  machine: Option<Machine<B>>,
+  pending: Vec<u8>,
+}
+
+impl<B> Feeder<B>
+where
+  B: Insert,
+{
+  pub(crate) fn new(builder: B, options: &RecognizeOptions) -> Self {
+    Feeder {
+      machine: Some(Machine::new(builder, options)),
+      pending: Vec::with_capacity(4),
+    }
+  }
+
+  /// The number of bytes needed before the machine can advance; at least one until the head is
+  /// complete, none afterwards.
+  pub(crate) fn wanted(&self) -> Result<usize, Error> {
+    let machine = match &self.machine {
+      Some(machine) => machine,
+      None => return Ok(0),
+    };
+
+    match machine.capacity().checked_sub(self.pending.len()) {
+      Some(missing) if missing > 0 => Ok(missing),
+      // Characters split across the end of the chunk are completed before it is handed over.
+      _ => utf8_debt(&self.pending).map_err(|e| e.at(machine.position())),
+    }
+  }
+
+  /// Takes bytes from the input until the head is complete, returning the builder along with the
+  /// number of bytes taken, or `None` once the entire input was taken.
+  pub(crate) fn feed(&mut self, input: &[u8]) -> Result<Option<(B, usize)>, Error> {
+    let mut consumed = 0;
+
+    loop {
+      match self.wanted() {
+        Ok(0) => (),
+        Ok(_) if consumed == input.len() => return Ok(None),
+        Ok(wanted) => {
+          let amount = std::cmp::min(wanted, input.len() - consumed);
+          self.pending.extend_from_slice(&input[consumed..consumed + amount]);
+          consumed += amount;
+          continue;
+        }
+        Err(e) => return Err(self.fail(e)),
+      }
+
+      match self.advance() {
+        Ok(Some(builder)) => return Ok(Some((builder, consumed))),
+        Ok(None) => (),
+        Err(e) => return Err(self.fail(e)),
+      }
+    }
+  }
+
+  /// Handles the end of the input; whatever was buffered is handed over as a short read followed by
+  /// an empty one, which fails unless it completes the head.
+  pub(crate) fn eof(&mut self) -> Result<B, Error> {
+    let result = match self.machine.as_mut() {
+      Some(machine) => match utf8_debt(&self.pending) {
+        Ok(0) => Ok(()),
+        Ok(_) | Err(_) => Err(Error::InvalidUtf8(machine.position())),
+      },
+      None => Err(finished()),
+    };
+
+    if let Err(e) = result {
+      return Err(self.fail(e));
+    }
+
+    if !self.pending.is_empty() {
+      match self.advance() {
+        Ok(Some(builder)) => return Ok(builder),
+        Ok(None) => (),
+        Err(e) => return Err(self.fail(e)),
+      }
+    }
+
+    match self.advance() {
+      Ok(Some(builder)) => Ok(builder),
+      Ok(None) => Err(self.fail(Error::UnexpectedEof(Position::default()))),
+      Err(e) => Err(self.fail(e)),
+    }
+  }
+
+  // Hands the pending bytes to the machine as a single chunk.
+  fn advance(&mut self) -> Result<Option<B>, Error> {
+    let mut machine = self.machine.take().ok_or_else(finished)?;
+    let chunk = decode(&self.pending).map_err(|e| e.at(machine.position()))?;
+    let done = machine.advance(chunk)?;
+    self.pending.clear();
+
+    if done {
+      return machine.finish().map(Some);
+    }
+
+    self.machine = Some(machine);
+    Ok(None)
+  }
+
+  // Errors are final; the machine is dropped so that nothing else is parsed.
+  fn fail(&mut self, error: Error) -> Error {
+    self.machine = None;
+    error
+  }
+}
+
+fn finished() -> Error {
+  std::io::Error::new(std::io::ErrorKind::InvalidInput, "Parser already finished").into()
+}
+
+const TERMINATOR: &[u8] = b"\r\n\r\n";
+
+// Counts how much of the CR LF CR LF terminator has been seen, across buffer boundaries. The start
+// line is kept while it is read, as simple requests and the HTTP/2 preface end elsewhere.
+#[derive(Debug)]
+pub(crate) struct Terminator {
+  matched: usize,
+  first: Option<Vec<u8>>,
+  simple: bool,
+  preface: Option<usize>,
+}
+
+impl Terminator {
+  pub(crate) fn new(options: &RecognizeOptions) -> Self {
+    Terminator {
+      matched: 0,
+      first: Some(Vec::new()),
+      simple: options.http09,
+      preface: None,
+    }
+  }
+
+  // Returns the number of bytes from the input that belong to the head, and whether the head is
+  // complete after consuming them.
+  pub(crate) fn scan(&mut self, input: &[u8]) -> (usize, bool) {
+    for (index, byte) in input.iter().enumerate() {
+      if let Some(remaining) = self.preface {
+        let amount = std::cmp::min(remaining, input.len() - index);
+        self.preface = Some(remaining - amount);
+        return (index + amount, remaining == amount);
+      }
+
+      self.matched = match *byte {
+        b if b == TERMINATOR[self.matched] => self.matched + 1,
+        b'\r' => 1,
+        _ => 0,
+      };
+
+      if self.matched == TERMINATOR.len() && self.first.is_none() {
+        return (index + 1, true);
+      }
+
+      if let Some(line) = self.first.as_mut() {
+        line.push(*byte);
+
+        if line.ends_with(b"\r\n") {
+          let line = self.first.take().unwrap_or_default();
+          let line = &line[0..line.len() - 2];
+
+          // Empty lines preceding the start line are skipped, as they are by `Machine`.
+          if line.is_empty() {
+            self.first = Some(Vec::new());
+            self.matched = 2;
+            continue;
+          }
+
+          if line == PREFACE_LINE.as_bytes() {
+            self.preface = Some(PREFACE.len() - line.len() - 2);
+          } else if self.simple && std::str::from_utf8(line).map(is_simple_request) == Ok(true) {
+            return (index + 1, true);
+          }
+        }
+      }
+    }
+
+    (input.len(), false)
+  }
+}
+
+pub(crate) fn exhausted(head: &[u8]) -> Error {
+  let lines = head.windows(2).filter(|pair| pair == b"\r\n").count();
+  Error::UnexpectedEof(Position {
+    offset: head.len(),
+    line: lines + 1,
+  })
+}
+
+pub(crate) fn insert_lines<B>(head: &[u8], mut builder: B, budget: &mut Budget) -> Result<B, Error>
+where
+  B: Insert,
+{
+  // Empty lines preceding the start line were skipped while scanning.
+  let mut head = head;
+  while let Some(rest) = head.strip_prefix(&b"\r\n"[..]) {
+    head = rest;
+  }
+
+  // Only simple requests end without an empty line.
+  let simple = !head.ends_with(TERMINATOR);
+  let end = if simple { 2 } else { TERMINATOR.len() };
+  let mut rest = &head[0..head.len().saturating_sub(end)];
+
+  loop {
+    let (line, next) = match rest.windows(2).position(|pair| pair == b"\r\n") {
+      Some(end) => (&rest[0..end], Some(&rest[end + 2..])),
+      None => (rest, None),
+    };
+
+    budget.current(line.len())?;
+    let line = String::from_utf8(line.to_vec()).map_err(|_| Error::InvalidUtf8(budget.position()))?;
+
+    if budget.lines == 0 && builder.is_preface(&line) {
+      return match head == PREFACE.as_bytes() {
+        true => Err(Error::Http2Preface),
+        false => Err(Error::InvalidRequestLine(budget.position())),
+      };
+    }
+
+    budget.line(line.len())?;
+
+    if simple && builder.insert_simple(&line) {
+      return Ok(builder);
+    }
+
+    builder.insert_line(line)?;
+
+    match next {
+      Some(next) => rest = next,
+      None => {
+        builder.finish()?;
+        return Ok(builder);
+      }
+    }
+  }
+}
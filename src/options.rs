@@ -0,0 +1,188 @@
+use std::time::Duration;
+
+/// Identifies which of the [`Limits`] was exceeded while reading a head, allowing callers to
+/// respond with the appropriate status:
+///
+/// ```rust
+/// use async_std::task::block_on;
+/// use elaine::{recognize_with, Error, LimitExceeded, Limits, RecognizeOptions};
+///
+/// block_on(async {
+///   let options = RecognizeOptions {
+///     limits: Limits { request_line: 10, ..Limits::default() },
+///     ..RecognizeOptions::default()
+///   };
+///   let mut req: &[u8] = b"GET /a-very-long-path HTTP/1.1\r\n\r\n";
+///   match recognize_with(&mut req, &options).await {
+///     Err(Error::LimitExceeded(limit, _)) => {
+///       assert_eq!(limit, LimitExceeded::RequestLine);
+///       assert_eq!(limit.status(), 414);
+///     }
+///     other => panic!("unexpected result: {:?}", other),
+///   }
+/// });
+/// ```
+///
+/// [`Limits`]: struct.Limits.html
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LimitExceeded {
+  RequestLine,
+  HeaderLine,
+  HeaderCount,
+  HeadSize,
+}
+
+impl LimitExceeded {
+  /// The response status code that best describes the exceeded limit; `414 URI Too Long` for the
+  /// request line, `431 Request Header Fields Too Large` for everything else.
+  pub fn status(&self) -> u16 {
+    match self {
+      LimitExceeded::RequestLine => 414,
+      _ => 431,
+    }
+  }
+}
+
+impl std::fmt::Display for LimitExceeded {
+  fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+    let name = match self {
+      LimitExceeded::RequestLine => "request line length",
+      LimitExceeded::HeaderLine => "header line length",
+      LimitExceeded::HeaderCount => "header count",
+      LimitExceeded::HeadSize => "head size",
+    };
+    write!(formatter, "Exceeded maximum {}", name)
+  }
+}
+
+impl std::error::Error for LimitExceeded {}
+
+/// Upper bounds on the amount of data that will be buffered while reading a head. Line lengths do
+/// not include the terminating CR LF; for response heads the `request_line` limit applies to the
+/// status line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+  pub request_line: usize,
+  pub header_line: usize,
+  pub header_count: usize,
+  pub head: usize,
+}
+
+impl Default for Limits {
+  fn default() -> Self {
+    Limits {
+      request_line: 8 * 1024,
+      header_line: 8 * 1024,
+      header_count: 100,
+      head: 64 * 1024,
+    }
+  }
+}
+
+/// Which of the [`Timeouts`] elapsed while reading a head.
+///
+/// [`Timeouts`]: struct.Timeouts.html
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Timeout {
+  Head,
+  Idle,
+}
+
+impl std::fmt::Display for Timeout {
+  fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+    match self {
+      Timeout::Head => write!(formatter, "Timed out reading head"),
+      Timeout::Idle => write!(formatter, "Timed out waiting for data"),
+    }
+  }
+}
+
+/// Time limits for [`recognize_with_timeout`], guarding against clients that send their head slowly
+/// (or not at all). Neither is enforced by default.
+///
+/// [`recognize_with_timeout`]: fn.recognize_with_timeout.html
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Timeouts {
+  /// The longest the entire head may take to arrive, measured from the start of the call.
+  pub head: Option<Duration>,
+  /// The longest to wait for each read to return data.
+  pub idle: Option<Duration>,
+}
+
+/// How header values continued on the next line with leading whitespace ([obs-fold][fold]) are
+/// handled. Servers may do either, user agents reading responses should unfold; requests are
+/// rejected by default and responses unfolded (see [`RecognizeOptions::response`]).
+///
+/// [`RecognizeOptions::response`]: struct.RecognizeOptions.html#method.response
+///
+/// [fold]: https://tools.ietf.org/html/rfc7230#section-3.2.4
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ObsFold {
+  /// Fail with an [`Error::InvalidHeader`](enum.Error.html#variant.InvalidHeader).
+  #[default]
+  Reject,
+  /// Replace the line break and leading whitespace with a single space.
+  Unfold,
+}
+
+/// How header lines that are not `name: value` fields (no colon, or a name that is not a valid
+/// token) are handled. Lenient parsing is the default for now; the next major version will default
+/// to strict parsing.
+///
+/// ```rust
+/// use async_std::task::block_on;
+/// use elaine::{recognize_with, Error, HeaderParsing, RecognizeOptions};
+///
+/// block_on(async {
+///   let mut req: &[u8] = b"GET / HTTP/1.1\r\nFOOBAR\r\nHost: example.com\r\n\r\n";
+///   let head = recognize_with(&mut req, &RecognizeOptions::default()).await.unwrap();
+///   assert_eq!(head.diagnostics()[0].line, "FOOBAR");
+///
+///   let options = RecognizeOptions {
+///     header_parsing: HeaderParsing::Strict,
+///     ..RecognizeOptions::default()
+///   };
+///   let mut req: &[u8] = b"GET / HTTP/1.1\r\nFOOBAR\r\nHost: example.com\r\n\r\n";
+///   let result = recognize_with(&mut req, &options).await;
+///   assert!(matches!(result, Err(Error::InvalidHeader(_))));
+/// });
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum HeaderParsing {
+  /// Fail with an [`Error::InvalidHeader`](enum.Error.html#variant.InvalidHeader).
+  Strict,
+  /// Skip the line, recording it as a [`Diagnostic`](struct.Diagnostic.html) on the head.
+  #[default]
+  Lenient,
+}
+
+/// Configuration for [`recognize_with`] and [`recognize_response_with`].
+///
+/// [`recognize_with`]: fn.recognize_with.html
+/// [`recognize_response_with`]: fn.recognize_response_with.html
+#[derive(Debug, Clone, Default)]
+pub struct RecognizeOptions {
+  pub limits: Limits,
+  /// Accept HTTP/0.9 simple requests (`GET /path` followed by a single CR LF), which end the head
+  /// after the request line and have a version of [`RequestVersion::Simple`]. Disabled by default;
+  /// while enabled the request line is read in smaller steps so that nothing past its CR LF is
+  /// consumed.
+  ///
+  /// [`RequestVersion::Simple`]: enum.RequestVersion.html#variant.Simple
+  pub http09: bool,
+  pub obs_fold: ObsFold,
+  pub header_parsing: HeaderParsing,
+}
+
+impl RecognizeOptions {
+  /// The options used to read responses when none are given, e.g. by [`recognize_response`] and
+  /// the client; the defaults, except that folded header values are unfolded as user agents must.
+  ///
+  /// [`recognize_response`]: fn.recognize_response.html
+  pub fn response() -> Self {
+    RecognizeOptions {
+      obs_fold: ObsFold::Unfold,
+      ..RecognizeOptions::default()
+    }
+  }
+}
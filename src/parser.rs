@@ -0,0 +1,147 @@
+use crate::error::Error;
+use crate::head::{Builder, Head};
+use crate::machine::{Feeder, Insert};
+use crate::options::RecognizeOptions;
+use crate::response::{ResponseBuilder, ResponseHead};
+
+/// The result of feeding bytes to a [`Parser`] or [`ResponseParser`].
+///
+/// [`Parser`]: struct.Parser.html
+/// [`ResponseParser`]: struct.ResponseParser.html
+#[derive(Debug)]
+pub enum Status<H = Head> {
+  /// Every byte was taken and the head is not complete yet.
+  NeedMore,
+  /// The head is complete, having taken this many bytes of the last input; the rest of the input
+  /// follows the head.
+  Complete(H, usize),
+  Error(Error),
+}
+
+impl<H> Status<H> {
+  fn from_feed<B>(result: Result<Option<(B, usize)>, Error>, collect: fn(B) -> H) -> Self {
+    match result {
+      Ok(None) => Status::NeedMore,
+      Ok(Some((builder, consumed))) => Status::Complete(collect(builder), consumed),
+      Err(e) => Status::Error(e),
+    }
+  }
+
+  fn from_eof<B>(result: Result<B, Error>, collect: fn(B) -> H) -> Self {
+    match result {
+      Ok(builder) => Status::Complete(collect(builder), 0),
+      Err(e) => Status::Error(e),
+    }
+  }
+}
+
+/// A request head parser that is handed bytes as they arrive instead of reading them itself, for
+/// event loops, completion based I/O or data that is already in memory. [`recognize`] and
+/// [`blocking::recognize`] are built on it and behave identically.
+///
+/// Any amount of input may be fed at once; the parser takes only the bytes belonging to the head.
+/// Readers that must not consume anything past the head can limit each read to [`wanted`] bytes.
+/// Once a status other than `NeedMore` was returned the parser is finished.
+///
+/// ```rust
+/// use elaine::{Parser, Status};
+///
+/// let mut parser = Parser::new();
+/// assert!(matches!(parser.feed(b"GET /elaine HTTP/1.1\r\nHost: exa"), Status::NeedMore));
+///
+/// match parser.feed(b"mple.com\r\n\r\nbody") {
+///   Status::Complete(head, consumed) => {
+///     assert_eq!(head.path(), Some("/elaine".to_string()));
+///     assert_eq!(consumed, 12);
+///   }
+///   other => panic!("unexpected {:?}", other),
+/// }
+/// ```
+///
+/// [`recognize`]: fn.recognize.html
+/// [`blocking::recognize`]: blocking/fn.recognize.html
+/// [`wanted`]: #method.wanted
+#[derive(Debug)]
+pub struct Parser {
+  feeder: Feeder<Builder>,
+}
+
+impl Default for Parser {
+  fn default() -> Self {
+    Parser::new()
+  }
+}
+
+impl Parser {
+  pub fn new() -> Self {
+    Parser::with_options(&RecognizeOptions::default())
+  }
+
+  pub fn with_options(options: &RecognizeOptions) -> Self {
+    Parser {
+      feeder: Feeder::new(Builder::with_options(options), options),
+    }
+  }
+
+  pub fn feed(&mut self, input: &[u8]) -> Status {
+    Status::from_feed(self.feeder.feed(input), Builder::collect::<Head>)
+  }
+
+  /// Signals that the input ended; unless the bytes fed so far complete the head (only possible
+  /// for HTTP/0.9 simple requests) the status is an `Error::UnexpectedEof`.
+  pub fn eof(&mut self) -> Status {
+    Status::from_eof(self.feeder.eof(), Builder::collect::<Head>)
+  }
+
+  /// The most bytes the next input may hold without reaching past the end of the head.
+  pub fn wanted(&self) -> usize {
+    wanted(&self.feeder)
+  }
+}
+
+/// The [`Parser`] counterpart for response heads, used by [`recognize_response`].
+///
+/// [`Parser`]: struct.Parser.html
+/// [`recognize_response`]: fn.recognize_response.html
+#[derive(Debug)]
+pub struct ResponseParser {
+  feeder: Feeder<ResponseBuilder>,
+}
+
+impl Default for ResponseParser {
+  fn default() -> Self {
+    ResponseParser::new()
+  }
+}
+
+impl ResponseParser {
+  pub fn new() -> Self {
+    ResponseParser::with_options(&RecognizeOptions::response())
+  }
+
+  pub fn with_options(options: &RecognizeOptions) -> Self {
+    ResponseParser {
+      feeder: Feeder::new(ResponseBuilder::with_options(options), options),
+    }
+  }
+
+  pub fn feed(&mut self, input: &[u8]) -> Status<ResponseHead> {
+    Status::from_feed(self.feeder.feed(input), ResponseBuilder::collect::<ResponseHead>)
+  }
+
+  pub fn eof(&mut self) -> Status<ResponseHead> {
+    Status::from_eof(self.feeder.eof(), ResponseBuilder::collect::<ResponseHead>)
+  }
+
+  pub fn wanted(&self) -> usize {
+    wanted(&self.feeder)
+  }
+}
+
+// Invalid input is reported by the next call to `feed`, so asking for a single byte is enough.
+fn wanted<B>(feeder: &Feeder<B>) -> usize
+where
+  B: Insert,
+{
+  feeder.wanted().unwrap_or(1)
+}
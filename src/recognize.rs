@@ -1,312 +1,120 @@
-use async_std::io::Read;
-use async_std::prelude::*;
-use std::io::{Error, ErrorKind};
+use futures_io::{AsyncRead, AsyncWrite};
+use std::future::poll_fn;
 use std::marker::Unpin;
+use std::pin::Pin;
 
+use crate::error::Error;
 use crate::head::{Builder, Head};
+use crate::machine::{Feeder, Insert};
+use crate::options::RecognizeOptions;
+use crate::response::{ResponseBuilder, ResponseHead};
 
-#[derive(Debug)]
-enum Capacity {
-  One,
-  Two,
-  Three,
-  Four,
-}
-
-#[derive(Debug, Default)]
-struct Stack(Option<String>, Option<String>);
-
-impl Stack {
-  fn push(&mut self, content: String) {
-    self.0 = self.1.take();
-    self.1 = Some(content);
-  }
-
-  fn last_mut(&mut self) -> Option<&mut String> {
-    self.1.as_mut()
-  }
-
-  fn fin(mut self) -> Option<String> {
-    self.1.take()
-  }
-
-  fn pop(&mut self) -> Option<String> {
-    self.0.take()
-  }
+pub(crate) async fn read_some<R>(reader: &mut R, buf: &mut [u8]) -> Result<usize, std::io::Error>
+where
+  R: AsyncRead + Unpin,
+{
+  poll_fn(|cx| Pin::new(&mut *reader).poll_read(cx, buf)).await
 }
 
-async fn fill_utf8<R>(original: &[u8], reader: R) -> Result<String, Error>
+pub(crate) async fn write_all<W>(mut writer: W, mut buf: &[u8]) -> Result<(), Error>
 where
-  R: Read + Unpin,
+  W: AsyncWrite + Unpin,
 {
-  match std::str::from_utf8(original) {
-    Ok(c) => Ok(String::from(c)),
-    Err(e) => match e.error_len() {
-      None => {
-        let (valid, after_valid) = original.split_at(e.valid_up_to());
-        let stage = std::str::from_utf8(valid).map_err(|e| Error::new(ErrorKind::InvalidData, format!("{:?}", e)))?;
-        match after_valid {
-          [first, second, third] => {
-            let mut bytes = reader.bytes();
-            let fourth = bytes.next().await.ok_or(Error::from(ErrorKind::InvalidData))??;
-            match std::str::from_utf8(&[*first, *second, *third, fourth]) {
-              Ok(rest) => Ok(format!("{}{}", stage, rest)),
-              Err(e) => {
-                return Err(Error::new(
-                  ErrorKind::Other,
-                  format!("Failed utf8 decode after third byte: {:?}", e),
-                ));
-              }
-            }
-          }
-          [first, second] => {
-            let mut bytes = reader.bytes();
-            let third = bytes.next().await.ok_or(Error::from(ErrorKind::InvalidData))??;
-            match std::str::from_utf8(&[*first, *second, third]) {
-              Ok(rest) => Ok(format!("{}{}", stage, rest)),
-              Err(_e) => {
-                let fourth = bytes.next().await.ok_or(Error::from(ErrorKind::InvalidData))??;
-                match std::str::from_utf8(&[*first, *second, third, fourth]) {
-                  Ok(rest) => Ok(format!("{}{}", stage, rest)),
-                  Err(e) => {
-                    return Err(Error::new(
-                      ErrorKind::Other,
-                      format!("Failed utf8 decode after third byte: {:?}", e),
-                    ));
-                  }
-                }
-              }
-            }
-          }
-          [single] => {
-            let mut bytes = reader.bytes();
-            let second = bytes.next().await.ok_or(Error::from(ErrorKind::InvalidData))??;
-            match std::str::from_utf8(&[*single, second]) {
-              Ok(rest) => Ok(format!("{}{}", stage, rest)),
-              Err(e) => match e.error_len() {
-                None => {
-                  let third = bytes.next().await.ok_or(Error::from(ErrorKind::InvalidData))??;
-
-                  match std::str::from_utf8(&[*single, second, third]) {
-                    Ok(rest) => Ok(format!("{}{}", stage, rest)),
-                    Err(_e) => {
-                      let fourth = bytes.next().await.ok_or(Error::from(ErrorKind::InvalidData))??;
-                      match std::str::from_utf8(&[*single, second, third, fourth]) {
-                        Ok(rest) => Ok(format!("{}{}", stage, rest)),
-                        Err(e) => {
-                          return Err(Error::new(
-                            ErrorKind::Other,
-                            format!("Failed utf8 decode after third byte: {:?}", e),
-                          ));
-                        }
-                      }
-                    }
-                  }
-                }
-                Some(_) => {
-                  return Err(Error::new(
-                    ErrorKind::InvalidData,
-                    "Failed pulling second byte for utf-8 sequence",
-                  ));
-                }
-              },
-            }
-          }
-          _ => {
-            return Err(Error::new(
-              ErrorKind::InvalidData,
-              "Unable to determine correction for utf-8 boundary",
-            ))
-          }
-        }
-      }
-      Some(_) => {
-        return Err(Error::new(
-          ErrorKind::InvalidData,
-          format!("Invalid utf-8 sequence: {:?}", e),
-        ));
-      }
-    },
+  while !buf.is_empty() {
+    match poll_fn(|cx| Pin::new(&mut writer).poll_write(cx, buf)).await? {
+      0 => return Err(std::io::Error::from(std::io::ErrorKind::WriteZero).into()),
+      amount => buf = &buf[amount..],
+    }
   }
-}
 
-fn invalid_read<H>(mut stack: Stack) -> Result<H, Error> {
-  let message = match stack.last_mut() {
-    Some(value) => format!("Reader exhausted before terminating HTTP head (last read '{}')", value),
-    None => String::from("Reader exhausted before terminating HTTP head"),
-  };
-  Err(Error::new(ErrorKind::UnexpectedEof, message))
+  poll_fn(|cx| Pin::new(&mut writer).poll_flush(cx)).await?;
+  Ok(())
 }
 
 /// Reads from the reader, consuming valid utf-8 charactes in 1-4 byte sized chunks, stopping
 /// after successfully reaching a [CR LF sequence][rfc-1945]. The method will return an
-/// `std::io::Error` under any of the following conditions:
+/// [`Error`][error] under any of the following conditions:
 ///
 ///  - A failed read from the underlying reader.
 ///  - Invalid first line, per the [request line][req-line] specification.
 ///  - Invalid utf-8 encoding, at any point.
+///  - Exceeding any of the default [`Limits`][limits].
 ///
 ///  # Arguments
 ///
-///  * `reader` - Some implementation of [`async_std::io::Read`][read]
+///  * `reader` - Some implementation of [`futures_io::AsyncRead`][read], e.g. any
+///    [`async_std::io::Read`][async-std-read].
 ///
 /// [rfc-1945]: https://tools.ietf.org/html/rfc1945#section-4.1
 /// [req-line]: https://tools.ietf.org/html/rfc1945#section-5.1
-/// [read]: https://docs.rs/async-std/0.99.12/async_std/io/trait.Read.html
-pub async fn recognize<R>(mut reader: R) -> Result<Head, Error>
+/// [read]: https://docs.rs/futures-io/latest/futures_io/trait.AsyncRead.html
+/// [async-std-read]: https://docs.rs/async-std/latest/async_std/io/trait.Read.html
+/// [limits]: struct.Limits.html
+/// [error]: enum.Error.html
+pub async fn recognize<R>(reader: R) -> Result<Head, Error>
 where
-  R: Read + Unpin,
+  R: AsyncRead + Unpin,
 {
-  let mut marker = Capacity::Four;
-  let mut stack = Stack::default();
-  let mut builder = Builder::new();
-
-  loop {
-    let mut buf: Vec<u8> = match marker {
-      Capacity::Four => vec![0x13, 0x10, 0x13, 0x10],
-      Capacity::Three => vec![0x10, 0x13, 0x10],
-      Capacity::Two => vec![0x13, 0x10],
-      Capacity::One => vec![0x10],
-    };
-
-    let size = reader.read(&mut buf).await?;
-    let chunk = fill_utf8(&buf[0..size], &mut reader).await?;
-
-    let mut chars = chunk.chars();
-
-    match (marker, chars.next(), chars.next(), chars.next(), chars.next()) {
-      // clean terminal
-      (_, Some('\r'), Some('\n'), Some('\r'), Some('\n')) => break,
-      // terminal from previous '\r\n\r'
-      (Capacity::One, Some('\n'), _, _, _) => break,
-      // terminal from previous '\r\n'
-      (Capacity::Two, Some('\r'), Some('\n'), _, _) => break,
-      // non-terminal: had a cr lf but now working with something else
-      (Capacity::Two, Some(one), Some(two), _, _) => {
-        stack.push([one, two].iter().collect::<String>());
-        marker = Capacity::Four;
-      }
-      // terminal from previous '\r'
-      (Capacity::Three, Some('\n'), Some('\r'), Some('\n'), _) => break,
-      (Capacity::Three, Some('\n'), Some(one), None, None) => {
-        stack.push(format!("{}", one));
-        marker = Capacity::Four;
-      }
-      (Capacity::Three, Some('\n'), Some(one), Some(two), None) => {
-        stack.push(format!("{}{}", one, two));
-        marker = Capacity::Four;
-      }
-
-      // any char followed by '\r\n\r' - queue up single read
-      (_, Some(one), Some('\r'), Some('\n'), Some('\r')) => {
-        match stack.last_mut() {
-          Some(header) => header.push(one),
-          None => stack.push(one.to_string()),
-        }
-        marker = Capacity::One;
-      }
-
-      // any chars followed by '\r\n' - queue up double read
-      (_, Some(one), Some(two), Some('\r'), Some('\n')) => {
-        let mem = format!("{}{}", one, two);
-        match stack.last_mut() {
-          Some(header) => header.push_str(mem.as_str()),
-          None => stack.push(mem),
-        }
-        marker = Capacity::Two;
-      }
-
-      // any chars followed by '\r' - queue up triple read
-      (_, Some(one), Some(two), Some(three), Some('\r')) => {
-        let mem = format!("{}{}{}", one, two, three);
-        match stack.last_mut() {
-          Some(header) => header.push_str(mem.as_str()),
-          None => stack.push(mem),
-        }
-        marker = Capacity::Three;
-      }
+  recognize_with(reader, &RecognizeOptions::default()).await
+}
 
-      (_, Some(one), Some('\r'), None, None) => {
-        match stack.last_mut() {
-          Some(header) => {
-            header.push(one);
-          }
-          None => stack.push([one].iter().collect::<String>()),
-        }
-        marker = Capacity::Three;
-      }
+/// Behaves like [`recognize`], using the provided options. When one of the limits is exceeded the
+/// returned error will be an [`Error::LimitExceeded`].
+///
+/// [`recognize`]: fn.recognize.html
+/// [`Error::LimitExceeded`]: enum.Error.html#variant.LimitExceeded
+pub async fn recognize_with<R>(reader: R, options: &RecognizeOptions) -> Result<Head, Error>
+where
+  R: AsyncRead + Unpin,
+{
+  let builder = read_lines(reader, Builder::with_options(options), options).await?;
+  Ok(builder.collect::<Head>())
+}
 
-      (_, Some(one), Some(two), Some('\r'), None) => {
-        let mem = format!("{}{}", one, two);
-        match stack.last_mut() {
-          Some(header) => header.push_str(mem.as_str()),
-          None => stack.push(mem),
-        }
-        marker = Capacity::Three;
-      }
+/// Reads the head of an http response from the reader, providing the same guarantees as
+/// [`recognize`] - the reader will never be read past the CR LF sequence that terminates the
+/// head, leaving the body (if any) available to the caller. The first line is parsed as a
+/// [status line][status-line]. Folded header values are unfolded, see [`RecognizeOptions::response`].
+///
+/// [`recognize`]: fn.recognize.html
+/// [`RecognizeOptions::response`]: struct.RecognizeOptions.html#method.response
+/// [status-line]: https://tools.ietf.org/html/rfc7230#section-3.1.2
+pub async fn recognize_response<R>(reader: R) -> Result<ResponseHead, Error>
+where
+  R: AsyncRead + Unpin,
+{
+  recognize_response_with(reader, &RecognizeOptions::response()).await
+}
 
-      (_, Some('\r'), Some('\n'), Some(one), Some(two)) => {
-        stack.push([one, two].iter().collect::<String>());
-        marker = Capacity::Four;
-      }
+/// Behaves like [`recognize_response`], using the provided options.
+///
+/// [`recognize_response`]: fn.recognize_response.html
+pub async fn recognize_response_with<R>(reader: R, options: &RecognizeOptions) -> Result<ResponseHead, Error>
+where
+  R: AsyncRead + Unpin,
+{
+  let builder = read_lines(reader, ResponseBuilder::with_options(options), options).await?;
+  Ok(builder.collect::<ResponseHead>())
+}
 
-      (_, Some(one), Some('\r'), Some('\n'), Some(two)) => {
-        match stack.last_mut() {
-          Some(header) => {
-            header.push(one);
-          }
-          None => stack.push(one.to_string()),
-        }
-        stack.push(two.to_string());
-        marker = Capacity::Four;
-      }
+async fn read_lines<R, B>(mut reader: R, builder: B, options: &RecognizeOptions) -> Result<B, Error>
+where
+  R: AsyncRead + Unpin,
+  B: Insert,
+{
+  let mut feeder = Feeder::new(builder, options);
 
-      (_, Some(one), Some(two), Some(three), Some(four)) => {
-        let mem = format!("{}{}{}{}", one, two, three, four);
-        match stack.last_mut() {
-          Some(header) => header.push_str(mem.as_str()),
-          None => stack.push(mem),
-        }
-        marker = Capacity::Four;
-      }
-      (_, Some(one), Some(two), Some(three), None) => {
-        let mem = format!("{}{}{}", one, two, three);
-        match stack.last_mut() {
-          Some(header) => header.push_str(mem.as_str()),
-          None => stack.push(mem),
-        }
-        marker = Capacity::Four;
-      }
-      (_, Some(one), Some(two), None, None) => {
-        let mem = format!("{}{}", one, two);
-        match stack.last_mut() {
-          Some(header) => header.push_str(mem.as_str()),
-          None => stack.push(mem),
-        }
-        marker = Capacity::Four;
-      }
-      (_, Some(one), None, None, None) => {
-        match stack.last_mut() {
-          Some(header) => header.push(one),
-          None => stack.push(format!("{}", one)),
+  loop {
+    // Reads never request more than the machine can take without reaching past the end of the head.
+    let mut buf = vec![0; feeder.wanted()?];
+
+    match read_some(&mut reader, &mut buf).await? {
+      0 => return feeder.eof(),
+      size => {
+        if let Some((builder, _)) = feeder.feed(&buf[..size])? {
+          return Ok(builder);
         }
-        marker = Capacity::Four;
       }
-      (_, Some(_), None, Some(_), Some(_)) => return invalid_read(stack),
-      (_, Some(_), None, Some(_), None) => return invalid_read(stack),
-      (_, Some(_), None, None, Some(_)) => return invalid_read(stack),
-      (_, Some(_), Some(_), None, Some(_)) => return invalid_read(stack),
-      (_, None, _, _, _) => return invalid_read(stack),
-    }
-
-    if let Some(complete) = stack.pop() {
-      builder = builder.insert(complete)?;
     }
   }
-
-  if let Some(last) = stack.fin() {
-    builder = builder.insert(last)?;
-  }
-
-  Ok(builder.collect::<Head>())
 }
@@ -0,0 +1,263 @@
+use crate::error::{Diagnostic, Error, Position};
+use crate::framing::{chunked, parse_content_length, BodyFraming, CONTENT_LENGTH};
+use crate::head::{insert_field, Fields, Header, RequestVersion};
+use crate::headers::Headers;
+use crate::options::{HeaderParsing, ObsFold, RecognizeOptions};
+
+#[derive(Debug)]
+struct StatusLine {
+  version: RequestVersion,
+  code: u16,
+  reason: String,
+}
+
+fn parse_status_code(input: &str) -> Result<u16, Error> {
+  if input.len() != 3 || !input.bytes().all(|b| b.is_ascii_digit()) {
+    return Err(Error::InvalidStatusLine(Position::default()));
+  }
+
+  input
+    .parse::<u16>()
+    .map_err(|_| Error::InvalidStatusLine(Position::default()))
+}
+
+fn parse_status_line(input: String) -> Result<StatusLine, Error> {
+  let mut splits = input.splitn(3, ' ');
+  match (splits.next(), splits.next(), splits.next()) {
+    (Some(version), Some(code), reason) => {
+      let version = RequestVersion::parse(version)?;
+      let code = parse_status_code(code)?;
+
+      Ok(StatusLine {
+        version,
+        code,
+        reason: String::from(reason.unwrap_or("")),
+      })
+    }
+    _ => Err(Error::InvalidStatusLine(Position::default())),
+  }
+}
+
+#[derive(Debug)]
+pub struct ResponseBuilder {
+  inner: ResponseHead,
+  position: Position,
+  fold: ObsFold,
+  parsing: HeaderParsing,
+}
+
+impl Default for ResponseBuilder {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl ResponseBuilder {
+  /// Starts a builder that unfolds folded header values, as user agents reading responses must.
+  pub fn new() -> Self {
+    ResponseBuilder {
+      inner: ResponseHead::default(),
+      position: Position::default(),
+      fold: ObsFold::Unfold,
+      parsing: HeaderParsing::default(),
+    }
+  }
+
+  pub(crate) fn with_options(options: &RecognizeOptions) -> Self {
+    ResponseBuilder::new()
+      .obs_fold(options.obs_fold)
+      .header_parsing(options.header_parsing)
+  }
+
+  /// Sets how header lines continuing the previous value with leading whitespace are handled.
+  pub fn obs_fold(self, fold: ObsFold) -> Self {
+    ResponseBuilder { fold, ..self }
+  }
+
+  /// Sets whether malformed header lines fail parsing or are collected as diagnostics.
+  pub fn header_parsing(self, parsing: HeaderParsing) -> Self {
+    ResponseBuilder { parsing, ..self }
+  }
+
+  pub fn collect<D>(self) -> ResponseHead
+  where
+    D: From<ResponseBuilder>,
+  {
+    self.inner
+  }
+
+  pub fn len(&self) -> usize {
+    self.inner._headers.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.inner._headers.is_empty()
+  }
+
+  pub fn insert(mut self, line: String) -> Result<Self, Error> {
+    let position = self.position;
+    self.position.advance(line.len());
+
+    if self.inner._status.is_none() {
+      let status = parse_status_line(line).map_err(|e| e.at(position))?;
+
+      return Ok(ResponseBuilder {
+        inner: ResponseHead {
+          _status: Some(status),
+          ..self.inner
+        },
+        ..self
+      });
+    }
+
+    insert_field(&mut self.inner, line, position, self.fold, self.parsing)?;
+    Ok(self)
+  }
+}
+
+/// The parsed head of an http response, as returned by [`recognize_response`].
+///
+/// [`recognize_response`]: fn.recognize_response.html
+#[derive(Debug, Default)]
+pub struct ResponseHead {
+  _headers: Headers,
+  _diagnostics: Vec<Diagnostic>,
+  _status: Option<StatusLine>,
+  _len: Option<usize>,
+}
+
+impl From<ResponseBuilder> for ResponseHead {
+  fn from(builder: ResponseBuilder) -> ResponseHead {
+    builder.inner
+  }
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl ResponseHead {
+  pub fn version(&self) -> Option<RequestVersion> {
+    self._status.as_ref().map(|s| s.version)
+  }
+
+  pub fn status(&self) -> Option<u16> {
+    self._status.as_ref().map(|s| s.code)
+  }
+
+  pub fn reason(&self) -> Option<String> {
+    self._status.as_ref().map(|s| s.reason.clone())
+  }
+
+  pub fn len(&self) -> Option<usize> {
+    self._len
+  }
+
+  pub fn find_header<S>(&self, target: S) -> Option<String>
+  where
+    S: std::fmt::Display,
+  {
+    self._headers.get(&target.to_string()).map(String::from)
+  }
+
+  /// How the body following the head is framed, based on the status code and headers; a transfer
+  /// coding takes precedence over a `Content-Length`. Responses to `HEAD` requests and successful
+  /// responses to `CONNECT` never have a body, which only the caller can know.
+  pub fn framing(&self) -> BodyFraming {
+    match (self.status(), chunked(&self._headers), self._len) {
+      (Some(100..=199), _, _) | (Some(204), _, _) | (Some(304), _, _) => BodyFraming::None,
+      (_, Some(true), _) => BodyFraming::Chunked,
+      (_, Some(false), _) => BodyFraming::CloseDelimited,
+      (_, None, Some(len)) => BodyFraming::Length(len),
+      (_, None, None) => BodyFraming::CloseDelimited,
+    }
+  }
+
+  /// Every header field of the head, in the order they were received.
+  pub fn headers(&self) -> &Headers {
+    &self._headers
+  }
+
+  /// The malformed header lines skipped while parsing leniently, in the order they were read.
+  pub fn diagnostics(&self) -> &[Diagnostic] {
+    &self._diagnostics
+  }
+}
+
+impl Fields for ResponseHead {
+  fn headers_mut(&mut self) -> &mut Headers {
+    &mut self._headers
+  }
+
+  fn diagnostics_mut(&mut self) -> &mut Vec<Diagnostic> {
+    &mut self._diagnostics
+  }
+
+  fn add_header(&mut self, header: Header) -> Result<(), Error> {
+    let Header(key, value) = header;
+
+    if key.eq_ignore_ascii_case(CONTENT_LENGTH) {
+      let len = parse_content_length(&value)?;
+
+      if self._len.is_some_and(|previous| previous != len) {
+        return Err(Error::InvalidContentLength(Position::default()));
+      }
+
+      self._len = Some(len);
+    }
+
+    self._headers.append(key, value);
+    Ok(())
+  }
+
+  fn unfolded(&mut self, name: &str, value: &str) -> Result<(), Error> {
+    if name.eq_ignore_ascii_case(CONTENT_LENGTH) {
+      self._len = Some(parse_content_length(value)?);
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{ResponseBuilder, ResponseHead};
+  use crate::RequestVersion;
+
+  #[test]
+  fn test_status_line() {
+    let head = ResponseBuilder::new()
+      .insert("HTTP/1.1 404 Not Found".to_string())
+      .unwrap()
+      .collect::<ResponseHead>();
+    assert_eq!(head.version(), Some(RequestVersion::RFC2616));
+    assert_eq!(head.status(), Some(404));
+    assert_eq!(head.reason(), Some("Not Found".to_string()));
+  }
+
+  #[test]
+  fn test_status_line_empty_reason() {
+    let head = ResponseBuilder::new()
+      .insert("HTTP/1.0 204".to_string())
+      .unwrap()
+      .collect::<ResponseHead>();
+    assert_eq!(head.status(), Some(204));
+    assert_eq!(head.reason(), Some(String::new()));
+  }
+
+  #[test]
+  fn test_status_line_bad_code() {
+    let result = ResponseBuilder::new().insert("HTTP/1.1 2000 OK".to_string());
+    assert!(result.is_err());
+  }
+
+  #[test]
+  fn test_default_unfolds() {
+    for builder in [ResponseBuilder::new(), ResponseBuilder::default()] {
+      let head = builder
+        .insert("HTTP/1.1 200 OK".to_string())
+        .and_then(|builder| builder.insert("X-Folded: one".to_string()))
+        .and_then(|builder| builder.insert(" two".to_string()))
+        .unwrap()
+        .collect::<ResponseHead>();
+      assert_eq!(head.find_header("x-folded"), Some("one two".to_string()));
+    }
+  }
+}
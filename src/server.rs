@@ -0,0 +1,556 @@
+//! A minimal HTTP/1.1 server built on [`recognize`](../fn.recognize.html), available with the
+//! `server` feature. Each connection is read with the same keep-alive rules as
+//! [`Connection`](../struct.Connection.html) and every request is passed to a handler, whose
+//! [`Response`] is serialized with a `Content-Length` matching its body.
+//!
+//! ```rust,no_run
+//! use async_std::prelude::*;
+//! use async_std::task::block_on;
+//! use elaine::server::{Request, Response, Server};
+//!
+//! block_on(async {
+//!   let server = Server::bind("0.0.0.0:8080").unwrap();
+//!
+//!   server
+//!     .serve(|mut request: Request| async move {
+//!       let mut body = Vec::new();
+//!       match request.body().read_to_end(&mut body).await {
+//!         Ok(_) => Response::new(200).unwrap().body(body),
+//!         Err(_) => Response::new(400).unwrap(),
+//!       }
+//!     })
+//!     .await;
+//! });
+//! ```
+//!
+//! [`Response`]: struct.Response.html
+use async_std::channel::{bounded, Receiver, Sender};
+use async_std::future::timeout;
+use async_std::net::{TcpListener, TcpStream};
+use async_std::task::spawn;
+use std::future::{poll_fn, Future};
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::pin::pin;
+use std::sync::{Arc, Mutex};
+use std::task::Poll;
+use std::time::Duration;
+
+use crate::body::Body;
+use crate::connection::{is_persistent, skip};
+use crate::cookie::{SetCookie, SET_COOKIE};
+use crate::error::{Error, Position};
+use crate::framing::{CONTENT_LENGTH, TRANSFER_ENCODING};
+use crate::head::{connection_options, is_field, Head, RequestMethod};
+use crate::headers::Headers;
+use crate::options::RecognizeOptions;
+use crate::recognize::{read_some, recognize_with, write_all};
+use crate::values::CONNECTION;
+
+// The longest a closed connection is read from before the socket is dropped.
+const LINGER: Duration = Duration::from_secs(2);
+
+/// A request passed to the handler, with its head and a reader over its body. The part of the body
+/// the handler leaves unread is skipped once the response has been written.
+#[derive(Debug)]
+pub struct Request {
+  head: Head,
+  body: Option<Body<TcpStream>>,
+  returned: Arc<Mutex<Option<Body<TcpStream>>>>,
+}
+
+impl Request {
+  pub fn head(&self) -> &Head {
+    &self.head
+  }
+
+  pub fn body(&mut self) -> &mut Body<TcpStream> {
+    self
+      .body
+      .as_mut()
+      .expect("request body is present until the request is dropped")
+  }
+}
+
+impl Drop for Request {
+  // Hands the body back to the connection so that it can be skipped before the next request.
+  fn drop(&mut self) {
+    if let (Some(body), Ok(mut returned)) = (self.body.take(), self.returned.lock()) {
+      *returned = Some(body);
+    }
+  }
+}
+
+/// A response returned by the handler. Its framing is derived from the body: a `Content-Length` is
+/// always added unless the status does not allow a body, and handlers cannot set `Content-Length`
+/// or `Transfer-Encoding` themselves.
+///
+/// ```rust
+/// use elaine::server::Response;
+///
+/// let response = Response::new(201)?.header("Location", "/things/1")?.body("created");
+/// assert_eq!(response.status(), 201);
+/// assert_eq!(response.headers().get("location"), Some("/things/1"));
+/// assert_eq!(response.contents(), b"created");
+/// # Ok::<(), elaine::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+  status: u16,
+  headers: Headers,
+  body: Vec<u8>,
+}
+
+impl Response {
+  /// A response with the given status, which has to be a three digit code; others fail with
+  /// `Error::InvalidStatusLine`.
+  pub fn new(status: u16) -> Result<Self, Error> {
+    match (100..=999).contains(&status) {
+      true => Ok(Response::with_status(status)),
+      false => Err(Error::InvalidStatusLine(Position::default())),
+    }
+  }
+
+  fn with_status(status: u16) -> Self {
+    Response {
+      status,
+      headers: Headers::new(),
+      body: Vec::new(),
+    }
+  }
+
+  /// Appends a header, failing with `Error::InvalidHeader` when the name is not a token, the value
+  /// holds control characters other than HTAB, or the header is one of the framing headers that
+  /// are derived from the body.
+  pub fn header<K, V>(mut self, name: K, value: V) -> Result<Self, Error>
+  where
+    K: Into<String>,
+    V: Into<String>,
+  {
+    let (name, value) = (name.into(), value.into());
+    let framing = name.eq_ignore_ascii_case(CONTENT_LENGTH) || name.eq_ignore_ascii_case(TRANSFER_ENCODING);
+
+    if framing || !is_field(&name, &value) {
+      return Err(Error::InvalidHeader(Position::default()));
+    }
+
+    self.headers.append(name, value);
+    Ok(self)
+  }
+
+  /// Appends a `Set-Cookie` header for the cookie.
+  pub fn cookie(mut self, cookie: &SetCookie) -> Self {
+    // Cookies are checked as they are built, and cannot hold control characters.
+    self.headers.append(SET_COOKIE, cookie.to_string());
+    self
+  }
+
+  pub fn body<B>(self, body: B) -> Self
+  where
+    B: Into<Vec<u8>>,
+  {
+    Response {
+      body: body.into(),
+      ..self
+    }
+  }
+
+  pub fn status(&self) -> u16 {
+    self.status
+  }
+
+  pub fn headers(&self) -> &Headers {
+    &self.headers
+  }
+
+  pub fn contents(&self) -> &[u8] {
+    &self.body
+  }
+
+  fn has_body(&self) -> bool {
+    !(100..200).contains(&self.status) && self.status != 204 && self.status != 304
+  }
+
+  // Serializes the response, leaving the body out when responding to a `HEAD` request.
+  fn to_bytes(&self, head_only: bool) -> Vec<u8> {
+    let mut out = format!("HTTP/1.1 {} {}\r\n", self.status, reason(self.status));
+
+    for (name, value) in &self.headers {
+      out.push_str(&format!("{}: {}\r\n", name, value));
+    }
+
+    if self.has_body() {
+      out.push_str(&format!("{}: {}\r\n", CONTENT_LENGTH, self.body.len()));
+    }
+
+    out.push_str("\r\n");
+
+    let mut bytes = out.into_bytes();
+
+    if self.has_body() && !head_only {
+      bytes.extend_from_slice(&self.body);
+    }
+
+    bytes
+  }
+}
+
+fn reason(status: u16) -> &'static str {
+  match status {
+    100 => "Continue",
+    101 => "Switching Protocols",
+    200 => "OK",
+    201 => "Created",
+    202 => "Accepted",
+    204 => "No Content",
+    301 => "Moved Permanently",
+    302 => "Found",
+    303 => "See Other",
+    304 => "Not Modified",
+    307 => "Temporary Redirect",
+    308 => "Permanent Redirect",
+    400 => "Bad Request",
+    401 => "Unauthorized",
+    403 => "Forbidden",
+    404 => "Not Found",
+    405 => "Method Not Allowed",
+    408 => "Request Timeout",
+    409 => "Conflict",
+    411 => "Length Required",
+    413 => "Content Too Large",
+    414 => "URI Too Long",
+    415 => "Unsupported Media Type",
+    422 => "Unprocessable Content",
+    429 => "Too Many Requests",
+    431 => "Request Header Fields Too Large",
+    500 => "Internal Server Error",
+    501 => "Not Implemented",
+    502 => "Bad Gateway",
+    503 => "Service Unavailable",
+    504 => "Gateway Timeout",
+    505 => "HTTP Version Not Supported",
+    _ => "",
+  }
+}
+
+/// Stops a running [`Server`] from accepting connections; see [`Server::shutdown_handle`].
+///
+/// [`Server`]: struct.Server.html
+/// [`Server::shutdown_handle`]: struct.Server.html#method.shutdown_handle
+#[derive(Debug, Clone)]
+pub struct Shutdown(Sender<()>);
+
+impl Shutdown {
+  pub fn shutdown(&self) {
+    self.0.close();
+  }
+}
+
+#[derive(Debug)]
+struct Config {
+  options: RecognizeOptions,
+  timeout: Duration,
+}
+
+/// Accepts connections and passes every request read from them to a handler.
+#[derive(Debug)]
+pub struct Server {
+  listener: TcpListener,
+  config: Config,
+  concurrency: usize,
+  shutdown: (Sender<()>, Receiver<()>),
+}
+
+impl Server {
+  /// Binds a listener to the address; the server is started with [`serve`].
+  ///
+  /// [`serve`]: #method.serve
+  pub fn bind<A>(addr: A) -> Result<Self, Error>
+  where
+    A: ToSocketAddrs,
+  {
+    let listener = std::net::TcpListener::bind(addr)?;
+
+    Ok(Server {
+      listener: TcpListener::from(listener),
+      config: Config {
+        options: RecognizeOptions::default(),
+        timeout: Duration::from_secs(30),
+      },
+      concurrency: 1024,
+      shutdown: bounded(1),
+    })
+  }
+
+  /// Options used when reading every request head.
+  pub fn options(self, options: RecognizeOptions) -> Self {
+    Server {
+      config: Config { options, ..self.config },
+      ..self
+    }
+  }
+
+  /// How long a connection may take to send a request head (including the idle time between
+  /// requests), to send the remainder of a body the handler left unread, and to receive a response.
+  /// Connections that time out are closed. Defaults to 30 seconds.
+  pub fn timeout(self, timeout: Duration) -> Self {
+    Server {
+      config: Config { timeout, ..self.config },
+      ..self
+    }
+  }
+
+  /// The number of connections served at once; further connections wait to be accepted until one
+  /// closes. Defaults to 1024.
+  pub fn concurrency(self, concurrency: usize) -> Self {
+    Server {
+      concurrency: concurrency.max(1),
+      ..self
+    }
+  }
+
+  pub fn local_addr(&self) -> Result<SocketAddr, Error> {
+    Ok(self.listener.local_addr()?)
+  }
+
+  /// Returns a handle that gracefully shuts the server down; no more connections are accepted,
+  /// connections waiting for their next request are closed, and requests already being handled
+  /// are responded to (with `Connection: close`) before [`serve`] returns.
+  ///
+  /// [`serve`]: #method.serve
+  pub fn shutdown_handle(&self) -> Shutdown {
+    Shutdown(self.shutdown.0.clone())
+  }
+
+  /// Accepts connections until shut down, calling the handler for every request.
+  pub async fn serve<F, T>(self, handler: F)
+  where
+    F: Fn(Request) -> T + Send + Sync + 'static,
+    T: Future<Output = Response> + Send + 'static,
+  {
+    let handler = Arc::new(handler);
+    let config = Arc::new(self.config);
+    let shutdown = self.shutdown.1;
+
+    // Each connection holds one of the channel's slots until it closes.
+    let (permits, released) = bounded::<()>(self.concurrency);
+
+    loop {
+      if let Either::Left(_) = select(shutdown.recv(), permits.send(())).await {
+        break;
+      }
+
+      let stream = match select(shutdown.recv(), self.listener.accept()).await {
+        Either::Left(_) => {
+          let _ = released.try_recv();
+          break;
+        }
+        Either::Right(Ok((stream, _))) => stream,
+        Either::Right(Err(_)) => {
+          let _ = released.try_recv();
+          continue;
+        }
+      };
+
+      let permit = Permit(released.clone());
+      let handler = handler.clone();
+      let config = config.clone();
+      let shutdown = shutdown.clone();
+
+      spawn(async move {
+        let _permit = permit;
+        handle(stream, &*handler, &config, &shutdown).await;
+      });
+    }
+
+    // Waits for every connection to release its slot.
+    for _ in 0..self.concurrency {
+      let _ = permits.send(()).await;
+    }
+  }
+}
+
+struct Permit(Receiver<()>);
+
+impl Drop for Permit {
+  fn drop(&mut self) {
+    let _ = self.0.try_recv();
+  }
+}
+
+enum Either<L, R> {
+  Left(L),
+  Right(R),
+}
+
+// Waits for the first of two futures to complete, preferring the left one.
+async fn select<L, R>(left: L, right: R) -> Either<L::Output, R::Output>
+where
+  L: Future,
+  R: Future,
+{
+  let (mut left, mut right) = (pin!(left), pin!(right));
+
+  poll_fn(|cx| {
+    if let Poll::Ready(output) = left.as_mut().poll(cx) {
+      return Poll::Ready(Either::Left(output));
+    }
+
+    right.as_mut().poll(cx).map(Either::Right)
+  })
+  .await
+}
+
+async fn handle<F, T>(stream: TcpStream, handler: &F, config: &Config, shutdown: &Receiver<()>)
+where
+  F: Fn(Request) -> T,
+  T: Future<Output = Response>,
+{
+  respond(&stream, handler, config, shutdown).await;
+
+  // Closing a socket with unread data resets the connection, which can discard the last response
+  // before the client reads it; the write side is closed first and whatever is left is read briefly.
+  if stream.shutdown(std::net::Shutdown::Write).is_ok() {
+    let mut scratch = [0u8; 1024];
+    let mut reader = &stream;
+    let _ = timeout(config.timeout.min(LINGER), async {
+      while read_some(&mut reader, &mut scratch).await? > 0 {}
+      Ok::<(), std::io::Error>(())
+    })
+    .await;
+  }
+}
+
+async fn respond<F, T>(stream: &TcpStream, handler: &F, config: &Config, shutdown: &Receiver<()>)
+where
+  F: Fn(Request) -> T,
+  T: Future<Output = Response>,
+{
+  let mut reader = stream.clone();
+
+  loop {
+    let next = timeout(config.timeout, recognize_with(&mut reader, &config.options));
+
+    let head = match select(shutdown.recv(), next).await {
+      Either::Right(Ok(Ok(head))) => head,
+      Either::Right(Ok(Err(e))) => {
+        let status = match e {
+          Error::LimitExceeded(kind, _) => kind.status(),
+          Error::UnexpectedEof(_) | Error::Http2Preface | Error::Io(_) => return,
+          _ => 400,
+        };
+        let mut response = Response::with_status(status);
+        response.headers.append(CONNECTION, "close");
+        let _ = timeout(config.timeout, write_all(stream, &response.to_bytes(false))).await;
+        return;
+      }
+      Either::Right(Err(_)) | Either::Left(_) => return,
+    };
+
+    let persistent = is_persistent(&head);
+    let head_only = head.method() == Some(RequestMethod::HEAD);
+    let body = match Body::new(&head, stream.clone()) {
+      Ok(body) => body,
+      Err(_) => return,
+    };
+
+    let returned = Arc::new(Mutex::new(None));
+    let request = Request {
+      head,
+      body: Some(body),
+      returned: returned.clone(),
+    };
+
+    let mut response = handler(request).await;
+
+    // A handler that kept the request around leaves the body unfinished, so the connection ends.
+    let body = returned.lock().ok().and_then(|mut returned| returned.take());
+    // A malformed `Connection` header set by the handler also ends the connection.
+    let options = connection_options(&response.headers).ok();
+    let closes = options
+      .as_ref()
+      .is_some_and(|options| options.iter().any(|option| option == "close"));
+    let keep_alive = persistent && body.is_some() && !shutdown.is_closed() && options.is_some() && !closes;
+
+    if !keep_alive && !closes {
+      response.headers.append(CONNECTION, "close");
+    }
+
+    match timeout(config.timeout, write_all(stream, &response.to_bytes(head_only))).await {
+      Ok(Ok(())) => (),
+      _ => return,
+    }
+
+    match body {
+      Some(mut body) if keep_alive => match timeout(config.timeout, skip(&mut body)).await {
+        Ok(Ok(())) => (),
+        _ => return,
+      },
+      _ => return,
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::Response;
+  use crate::cookie::SetCookie;
+
+  #[test]
+  fn test_response_bytes() {
+    let response = Response::new(200)
+      .and_then(|response| response.header("Server", "elaine"))
+      .unwrap()
+      .body("hey");
+    assert_eq!(
+      response.to_bytes(false),
+      b"HTTP/1.1 200 OK\r\nServer: elaine\r\nContent-Length: 3\r\n\r\nhey".to_vec()
+    );
+    assert_eq!(
+      response.to_bytes(true),
+      b"HTTP/1.1 200 OK\r\nServer: elaine\r\nContent-Length: 3\r\n\r\n".to_vec()
+    );
+    let cookie = SetCookie::new("sid", "abc").unwrap().http_only();
+    assert_eq!(
+      Response::new(204).unwrap().cookie(&cookie).to_bytes(false),
+      b"HTTP/1.1 204 No Content\r\nSet-Cookie: sid=abc; HttpOnly\r\n\r\n".to_vec()
+    );
+    assert_eq!(
+      Response::new(204).unwrap().to_bytes(false),
+      b"HTTP/1.1 204 No Content\r\n\r\n".to_vec()
+    );
+  }
+
+  #[test]
+  fn test_response_header_injection() {
+    assert!(Response::new(200)
+      .unwrap()
+      .header("Location", "/\r\nSet-Cookie: a=b")
+      .is_err());
+    assert!(Response::new(200).unwrap().header("Location", "/\nb").is_err());
+    assert!(Response::new(200)
+      .unwrap()
+      .header("X-Bad\r\nSet-Cookie", "a=b")
+      .is_err());
+    assert!(Response::new(200).unwrap().header("X Y", "a").is_err());
+    assert!(Response::new(200).unwrap().header("", "a").is_err());
+    assert!(Response::new(200).unwrap().header("X", "a\tb").is_ok());
+  }
+
+  #[test]
+  fn test_response_framing() {
+    assert!(Response::new(200).unwrap().header("Content-Length", "10").is_err());
+    assert!(Response::new(200)
+      .unwrap()
+      .header("transfer-encoding", "chunked")
+      .is_err());
+    assert_eq!(
+      Response::new(404).unwrap().body("gone").to_bytes(true),
+      b"HTTP/1.1 404 Not Found\r\nContent-Length: 4\r\n\r\n".to_vec()
+    );
+
+    assert!(Response::new(0).is_err());
+    assert!(Response::new(99).is_err());
+    assert!(Response::new(1000).is_err());
+    assert_eq!(Response::new(599).unwrap().status(), 599);
+  }
+}
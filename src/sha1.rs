@@ -0,0 +1,84 @@
+//! SHA-1 of FIPS 180-4, used only to compute the `Sec-WebSocket-Accept` value of the WebSocket
+//! handshake, where it is not relied on for security.
+
+pub(crate) fn digest(input: &[u8]) -> [u8; 20] {
+  let mut state: [u32; 5] = [0x6745_2301, 0xEFCD_AB89, 0x98BA_DCFE, 0x1032_5476, 0xC3D2_E1F0];
+
+  // The message is padded with a single set bit, zeros and its length in bits to a multiple of 64
+  // bytes.
+  let mut message = input.to_vec();
+  message.push(0x80);
+  message.resize(message.len().div_ceil(64) * 64, 0);
+  if message.len() - input.len() < 9 {
+    message.resize(message.len() + 64, 0);
+  }
+  let length = message.len();
+  message[length - 8..].copy_from_slice(&(input.len() as u64 * 8).to_be_bytes());
+
+  for block in message.chunks(64) {
+    let mut words = [0u32; 80];
+
+    for (index, word) in block.chunks(4).enumerate() {
+      words[index] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+    }
+    for index in 16..80 {
+      words[index] = (words[index - 3] ^ words[index - 8] ^ words[index - 14] ^ words[index - 16]).rotate_left(1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e] = state;
+
+    for (index, word) in words.iter().enumerate() {
+      let (f, k) = match index {
+        0..=19 => ((b & c) | (!b & d), 0x5A82_7999),
+        20..=39 => (b ^ c ^ d, 0x6ED9_EBA1),
+        40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1B_BCDC),
+        _ => (b ^ c ^ d, 0xCA62_C1D6),
+      };
+
+      let temp = a
+        .rotate_left(5)
+        .wrapping_add(f)
+        .wrapping_add(e)
+        .wrapping_add(k)
+        .wrapping_add(*word);
+      e = d;
+      d = c;
+      c = b.rotate_left(30);
+      b = a;
+      a = temp;
+    }
+
+    for (value, add) in state.iter_mut().zip([a, b, c, d, e]) {
+      *value = value.wrapping_add(add);
+    }
+  }
+
+  let mut output = [0u8; 20];
+  for (chunk, value) in output.chunks_mut(4).zip(state) {
+    chunk.copy_from_slice(&value.to_be_bytes());
+  }
+  output
+}
+
+#[cfg(test)]
+mod test {
+  use super::digest;
+
+  fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+  }
+
+  #[test]
+  fn test_digest() {
+    assert_eq!(hex(&digest(b"")), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+    assert_eq!(hex(&digest(b"abc")), "a9993e364706816aba3e25717850c26c9cd0d89d");
+    assert_eq!(
+      hex(&digest(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")),
+      "84983e441c3bd26ebaae4aa1f95129e5e54670f1"
+    );
+    assert_eq!(
+      hex(&digest(&[b'a'; 1_000_000])),
+      "34aa973cd4c4daa4f61eeb2bdbad27316534016f"
+    );
+  }
+}
@@ -0,0 +1,304 @@
+use std::borrow::Cow;
+
+use crate::error::{Error, Position};
+
+/// The request-target of a request line, in one of the four forms described by
+/// [RFC 7230 §5.3][forms]:
+///
+/// ```rust
+/// use elaine::RequestTarget;
+///
+/// let target = RequestTarget::parse("/search/caf%C3%A9?q=elaine+may&page=2").unwrap();
+/// assert_eq!(target.path(), Some("/search/caf%C3%A9"));
+/// assert_eq!(target.segments().collect::<Vec<_>>(), vec!["search", "café"]);
+/// assert_eq!(
+///   target.query_pairs().collect::<Vec<_>>(),
+///   vec![("q".to_string(), "elaine may".to_string()), ("page".to_string(), "2".to_string())]
+/// );
+///
+/// let target = RequestTarget::parse("example.com:443").unwrap();
+/// assert_eq!(target.host(), Some("example.com"));
+/// assert_eq!(target.port(), Some(443));
+/// ```
+///
+/// [forms]: https://tools.ietf.org/html/rfc7230#section-5.3
+#[derive(Debug, Clone, PartialEq)]
+pub enum RequestTarget {
+  /// An absolute path with an optional query, e.g. `/where?q=now`; the form used for most requests.
+  Origin { path: String, query: Option<String> },
+  /// A complete URI, e.g. `http://www.example.org/pub/`; used for requests made to proxies.
+  Absolute {
+    scheme: String,
+    authority: String,
+    path: String,
+    query: Option<String>,
+  },
+  /// The `host:port` of a tunnel destination; only used with `CONNECT`.
+  Authority(String),
+  /// The `*` of server-wide `OPTIONS` requests.
+  Asterisk,
+}
+
+fn is_scheme(input: &str) -> bool {
+  let mut chars = input.chars();
+  match chars.next() {
+    Some(first) if first.is_ascii_alphabetic() => {}
+    _ => return false,
+  }
+  chars.all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.')
+}
+
+fn split_query(input: &str) -> (String, Option<String>) {
+  match input.split_once('?') {
+    Some((path, query)) => (path.to_string(), Some(query.to_string())),
+    None => (input.to_string(), None),
+  }
+}
+
+// Separates the host from an explicit port, leaving the brackets of IPv6 literals in place.
+pub(crate) fn split_port(authority: &str) -> (&str, Option<&str>) {
+  let host_end = if authority.starts_with('[') {
+    authority.find(']').map(|end| end + 1).unwrap_or(authority.len())
+  } else {
+    authority.rfind(':').unwrap_or(authority.len())
+  };
+
+  match authority[host_end..].strip_prefix(':') {
+    Some(port) => (&authority[..host_end], Some(port)),
+    None => (authority, None),
+  }
+}
+
+fn is_authority(input: &str) -> bool {
+  if input.is_empty() || input.contains(['/', '?', '#', '@']) {
+    return false;
+  }
+
+  match split_port(input) {
+    (host, Some(port)) => !host.is_empty() && !port.is_empty() && port.parse::<u16>().is_ok(),
+    _ => false,
+  }
+}
+
+/// Decodes `%XX` escapes, leaving malformed escapes as they are. Sequences that do not decode to
+/// valid utf-8 are replaced with U+FFFD.
+pub(crate) fn percent_decode(input: &str) -> Cow<'_, str> {
+  if !input.contains('%') {
+    return Cow::Borrowed(input);
+  }
+
+  let bytes = input.as_bytes();
+  let mut decoded = Vec::with_capacity(bytes.len());
+  let mut index = 0;
+
+  while index < bytes.len() {
+    let escaped = bytes
+      .get(index + 1..index + 3)
+      .filter(|_| bytes[index] == b'%')
+      .and_then(|hex| std::str::from_utf8(hex).ok())
+      .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+    match escaped {
+      Some(byte) => {
+        decoded.push(byte);
+        index += 3;
+      }
+      None => {
+        decoded.push(bytes[index]);
+        index += 1;
+      }
+    }
+  }
+
+  Cow::Owned(String::from_utf8_lossy(&decoded).into_owned())
+}
+
+impl RequestTarget {
+  pub fn parse(input: &str) -> Result<Self, Error> {
+    let invalid = || Error::InvalidRequestTarget(Position::default());
+
+    if input.is_empty() || input.contains('#') || input.chars().any(|c| c.is_ascii_control()) {
+      return Err(invalid());
+    }
+
+    if input == "*" {
+      return Ok(RequestTarget::Asterisk);
+    }
+
+    if input.starts_with('/') {
+      let (path, query) = split_query(input);
+      return Ok(RequestTarget::Origin { path, query });
+    }
+
+    if let Some((scheme, rest)) = input.split_once("://") {
+      if !is_scheme(scheme) {
+        return Err(invalid());
+      }
+
+      let end = rest.find(['/', '?']).unwrap_or(rest.len());
+      let (authority, rest) = rest.split_at(end);
+
+      if authority.is_empty() {
+        return Err(invalid());
+      }
+
+      let (path, query) = split_query(rest);
+      return Ok(RequestTarget::Absolute {
+        scheme: scheme.to_string(),
+        authority: authority.to_string(),
+        path: if path.is_empty() { String::from("/") } else { path },
+        query,
+      });
+    }
+
+    if is_authority(input) {
+      return Ok(RequestTarget::Authority(input.to_string()));
+    }
+
+    Err(invalid())
+  }
+
+  /// The path, still percent-encoded; absent for the authority and asterisk forms.
+  pub fn path(&self) -> Option<&str> {
+    match self {
+      RequestTarget::Origin { path, .. } | RequestTarget::Absolute { path, .. } => Some(path.as_str()),
+      RequestTarget::Authority(_) | RequestTarget::Asterisk => None,
+    }
+  }
+
+  /// The query, without its leading `?` and still percent-encoded.
+  pub fn query(&self) -> Option<&str> {
+    match self {
+      RequestTarget::Origin { query, .. } | RequestTarget::Absolute { query, .. } => query.as_deref(),
+      RequestTarget::Authority(_) | RequestTarget::Asterisk => None,
+    }
+  }
+
+  pub fn scheme(&self) -> Option<&str> {
+    match self {
+      RequestTarget::Absolute { scheme, .. } => Some(scheme.as_str()),
+      _ => None,
+    }
+  }
+
+  /// The `host[:port]` of the absolute and authority forms, including any userinfo.
+  pub fn authority(&self) -> Option<&str> {
+    match self {
+      RequestTarget::Absolute { authority, .. } | RequestTarget::Authority(authority) => Some(authority.as_str()),
+      _ => None,
+    }
+  }
+
+  /// The host of the authority, with any userinfo and port removed. IPv6 literals keep their
+  /// brackets.
+  pub fn host(&self) -> Option<&str> {
+    let authority = self.authority()?;
+    let authority = authority.rsplit_once('@').map(|(_, host)| host).unwrap_or(authority);
+    Some(split_port(authority).0)
+  }
+
+  /// The port explicitly given in the authority; default ports are not inferred from the scheme.
+  pub fn port(&self) -> Option<u16> {
+    let authority = self.authority()?;
+    split_port(authority).1.and_then(|port| port.parse::<u16>().ok())
+  }
+
+  /// The percent-decoded segments of the path, e.g. `["a", "b c"]` for `/a/b%20c`. Encoded
+  /// slashes (`%2F`) remain within their segment.
+  pub fn segments(&self) -> impl Iterator<Item = Cow<'_, str>> {
+    let path = self.path().unwrap_or("");
+    let path = path.strip_prefix('/').unwrap_or(path);
+    path.split('/').filter(move |_| !path.is_empty()).map(percent_decode)
+  }
+
+  /// The `key=value` pairs of an `application/x-www-form-urlencoded` query, percent-decoded and with
+  /// `+` read as a space. Keys without a value are paired with an empty string.
+  pub fn query_pairs(&self) -> impl Iterator<Item = (String, String)> + '_ {
+    let decode = |input: &str| percent_decode(&input.replace('+', " ")).into_owned();
+
+    self
+      .query()
+      .unwrap_or("")
+      .split('&')
+      .filter(|pair| !pair.is_empty())
+      .map(move |pair| match pair.split_once('=') {
+        Some((key, value)) => (decode(key), decode(value)),
+        None => (decode(pair), String::new()),
+      })
+  }
+}
+
+impl std::fmt::Display for RequestTarget {
+  fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+    match self {
+      RequestTarget::Origin { path, .. } => write!(formatter, "{}", path)?,
+      RequestTarget::Absolute {
+        scheme,
+        authority,
+        path,
+        ..
+      } => write!(formatter, "{}://{}{}", scheme, authority, path)?,
+      RequestTarget::Authority(authority) => return write!(formatter, "{}", authority),
+      RequestTarget::Asterisk => return write!(formatter, "*"),
+    }
+
+    match self.query() {
+      Some(query) => write!(formatter, "?{}", query),
+      None => Ok(()),
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{percent_decode, RequestTarget};
+
+  #[test]
+  fn test_forms() {
+    assert_eq!(RequestTarget::parse("*").unwrap(), RequestTarget::Asterisk);
+    assert_eq!(
+      RequestTarget::parse("/where?q=now").unwrap(),
+      RequestTarget::Origin {
+        path: "/where".to_string(),
+        query: Some("q=now".to_string())
+      }
+    );
+
+    let absolute = RequestTarget::parse("http://user@[::1]:8080?x").unwrap();
+    assert_eq!(absolute.scheme(), Some("http"));
+    assert_eq!(absolute.authority(), Some("user@[::1]:8080"));
+    assert_eq!(absolute.host(), Some("[::1]"));
+    assert_eq!(absolute.port(), Some(8080));
+    assert_eq!(absolute.path(), Some("/"));
+    assert_eq!(absolute.query(), Some("x"));
+
+    let authority = RequestTarget::parse("example.com:443").unwrap();
+    assert_eq!(authority, RequestTarget::Authority("example.com:443".to_string()));
+    assert_eq!(authority.path(), None);
+  }
+
+  #[test]
+  fn test_invalid() {
+    assert!(RequestTarget::parse("").is_err());
+    assert!(RequestTarget::parse("example.com").is_err());
+    assert!(RequestTarget::parse("/a#b").is_err());
+    assert!(RequestTarget::parse("http:///path").is_err());
+    assert!(RequestTarget::parse("example.com:http").is_err());
+  }
+
+  #[test]
+  fn test_percent_decode() {
+    assert_eq!(percent_decode("a%20b"), "a b");
+    assert_eq!(percent_decode("100%"), "100%");
+    assert_eq!(percent_decode("%zz%2F"), "%zz/");
+    assert_eq!(percent_decode("%FF"), "\u{FFFD}");
+  }
+
+  #[test]
+  fn test_segments() {
+    let target = RequestTarget::parse("/a/b%2Fc/").unwrap();
+    assert_eq!(target.segments().collect::<Vec<_>>(), vec!["a", "b/c", ""]);
+    assert_eq!(RequestTarget::parse("/").unwrap().segments().count(), 0);
+    assert_eq!(RequestTarget::Asterisk.segments().count(), 0);
+  }
+}
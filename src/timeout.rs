@@ -0,0 +1,179 @@
+use async_std::task::sleep;
+use futures_io::AsyncRead;
+use std::future::Future;
+use std::marker::Unpin;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use crate::error::{Error, Position, TimedOut};
+use crate::head::Head;
+use crate::options::{RecognizeOptions, Timeout, Timeouts};
+use crate::recognize::{recognize_response_with, recognize_with};
+use crate::response::ResponseHead;
+
+// A sleep that is only replaced once it fires, so that moving its expiry (after every read, for the
+// idle timeout) does not allocate a new timer each time.
+#[derive(Default)]
+struct Timer(Option<Pin<Box<dyn Future<Output = ()> + Send>>>);
+
+impl Timer {
+  // Returns true once `at` has passed, otherwise arranges for the task to be woken then.
+  fn poll_expired(&mut self, cx: &mut Context, at: Instant) -> bool {
+    loop {
+      let now = Instant::now();
+
+      if now >= at {
+        self.0 = None;
+        return true;
+      }
+
+      let timer = self.0.get_or_insert_with(|| Box::pin(sleep(at - now)));
+
+      match timer.as_mut().poll(cx) {
+        Poll::Pending => return false,
+        Poll::Ready(()) => self.0 = None,
+      }
+    }
+  }
+}
+
+// Fails reads once either timeout elapses, keeping a copy of everything read.
+struct Timed<R> {
+  reader: R,
+  deadline: Option<Instant>,
+  idle: Option<Duration>,
+  last: Instant,
+  timers: (Timer, Timer),
+  elapsed: Option<Timeout>,
+  read: Vec<u8>,
+}
+
+impl<R> Timed<R> {
+  fn new(reader: R, timeouts: &Timeouts) -> Self {
+    let now = Instant::now();
+
+    Timed {
+      reader,
+      deadline: timeouts.head.map(|head| now + head),
+      idle: timeouts.idle,
+      last: now,
+      timers: (Timer::default(), Timer::default()),
+      elapsed: None,
+      read: Vec::new(),
+    }
+  }
+
+  // Replaces the error returned by the reader with the timeout that caused it.
+  fn check(self, error: Error) -> Error {
+    match self.elapsed {
+      Some(timeout) => Error::TimedOut(TimedOut {
+        timeout,
+        position: Position {
+          offset: self.read.len(),
+          line: self.read.iter().filter(|b| **b == b'\n').count() + 1,
+        },
+        partial: String::from_utf8_lossy(&self.read).into_owned(),
+      }),
+      None => error,
+    }
+  }
+}
+
+impl<R> AsyncRead for Timed<R>
+where
+  R: AsyncRead + Unpin,
+{
+  fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context, dest: &mut [u8]) -> Poll<Result<usize, std::io::Error>> {
+    let timed = &mut *self;
+
+    if let Poll::Ready(result) = Pin::new(&mut timed.reader).poll_read(cx, dest) {
+      if let Ok(size) = result {
+        timed.read.extend_from_slice(&dest[..size]);
+        timed.last = Instant::now();
+      }
+      return Poll::Ready(result);
+    }
+
+    let expired = match (timed.deadline, timed.idle) {
+      (Some(deadline), _) if timed.timers.0.poll_expired(cx, deadline) => Some(Timeout::Head),
+      (_, Some(idle)) if timed.timers.1.poll_expired(cx, timed.last + idle) => Some(Timeout::Idle),
+      _ => None,
+    };
+
+    match expired {
+      Some(timeout) => {
+        timed.elapsed = Some(timeout);
+        Poll::Ready(Err(std::io::ErrorKind::TimedOut.into()))
+      }
+      None => Poll::Pending,
+    }
+  }
+}
+
+/// Behaves like [`recognize_with`], failing with an [`Error::TimedOut`] when the head is not read
+/// within the [`Timeouts`]. The error keeps the bytes of the head read before the timeout elapsed.
+/// Available with the `async-std` feature.
+///
+/// ```rust
+/// use async_std::net::{TcpListener, TcpStream};
+/// use async_std::prelude::*;
+/// use async_std::task::block_on;
+/// use elaine::{recognize_with_timeout, Error, RecognizeOptions, Timeout, Timeouts};
+/// use std::time::Duration;
+///
+/// block_on(async {
+///   let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+///   let mut client = TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+///   let (mut stream, _) = listener.accept().await.unwrap();
+///   client.write_all(b"GET / HTTP/1.1\r\nHost: exa").await.unwrap();
+///
+///   let timeouts = Timeouts {
+///     idle: Some(Duration::from_millis(50)),
+///     ..Timeouts::default()
+///   };
+///
+///   match recognize_with_timeout(&mut stream, &RecognizeOptions::default(), &timeouts).await {
+///     Err(Error::TimedOut(timed_out)) => {
+///       assert_eq!(timed_out.timeout, Timeout::Idle);
+///       assert_eq!(timed_out.partial, "GET / HTTP/1.1\r\nHost: exa");
+///     }
+///     other => panic!("unexpected {:?}", other),
+///   }
+/// });
+/// ```
+///
+/// [`recognize_with`]: fn.recognize_with.html
+/// [`Error::TimedOut`]: enum.Error.html#variant.TimedOut
+/// [`Timeouts`]: struct.Timeouts.html
+pub async fn recognize_with_timeout<R>(
+  reader: R,
+  options: &RecognizeOptions,
+  timeouts: &Timeouts,
+) -> Result<Head, Error>
+where
+  R: AsyncRead + Unpin,
+{
+  let mut timed = Timed::new(reader, timeouts);
+  let result = recognize_with(&mut timed, options).await;
+  result.map_err(|e| timed.check(e))
+}
+
+/// Behaves like [`recognize_response_with`], within the [`Timeouts`]; see
+/// [`recognize_with_timeout`].
+///
+/// [`recognize_response_with`]: fn.recognize_response_with.html
+/// [`Timeouts`]: struct.Timeouts.html
+/// [`recognize_with_timeout`]: fn.recognize_with_timeout.html
+pub async fn recognize_response_with_timeout<R>(
+  reader: R,
+  options: &RecognizeOptions,
+  timeouts: &Timeouts,
+) -> Result<ResponseHead, Error>
+where
+  R: AsyncRead + Unpin,
+{
+  let mut timed = Timed::new(reader, timeouts);
+  let result = recognize_response_with(&mut timed, options).await;
+  result.map_err(|e| timed.check(e))
+}
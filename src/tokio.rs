@@ -0,0 +1,91 @@
+//! Versions of the parsing functions exported at the crate root for readers implementing
+//! [`tokio::io::AsyncRead`][read] (or [`tokio::io::AsyncBufRead`][buf-read]), available with the
+//! `tokio` feature. The returned [`Head`](../struct.Head.html) and errors are the same as those of
+//! the `async-std`/`futures-io` functions.
+//!
+//! [read]: https://docs.rs/tokio/latest/tokio/io/trait.AsyncRead.html
+//! [buf-read]: https://docs.rs/tokio/latest/tokio/io/trait.AsyncBufRead.html
+use ::tokio::io::{AsyncBufRead, AsyncRead, ReadBuf};
+use std::marker::Unpin;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use crate::error::Error;
+use crate::head::Head;
+use crate::options::RecognizeOptions;
+use crate::response::ResponseHead;
+
+// Adapts a tokio reader to the `futures-io` traits the parsing functions are implemented against.
+struct Compat<R>(R);
+
+impl<R> futures_io::AsyncRead for Compat<R>
+where
+  R: AsyncRead + Unpin,
+{
+  fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context, dest: &mut [u8]) -> Poll<Result<usize, std::io::Error>> {
+    let mut buf = ReadBuf::new(dest);
+    ready!(Pin::new(&mut self.0).poll_read(cx, &mut buf))?;
+    Poll::Ready(Ok(buf.filled().len()))
+  }
+}
+
+impl<R> futures_io::AsyncBufRead for Compat<R>
+where
+  R: AsyncBufRead + Unpin,
+{
+  fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<&[u8], std::io::Error>> {
+    Pin::new(&mut self.get_mut().0).poll_fill_buf(cx)
+  }
+
+  fn consume(mut self: Pin<&mut Self>, amount: usize) {
+    Pin::new(&mut self.0).consume(amount)
+  }
+}
+
+/// The tokio version of [`recognize`](../fn.recognize.html).
+pub async fn recognize<R>(reader: R) -> Result<Head, Error>
+where
+  R: AsyncRead + Unpin,
+{
+  crate::recognize(Compat(reader)).await
+}
+
+/// The tokio version of [`recognize_with`](../fn.recognize_with.html).
+pub async fn recognize_with<R>(reader: R, options: &RecognizeOptions) -> Result<Head, Error>
+where
+  R: AsyncRead + Unpin,
+{
+  crate::recognize_with(Compat(reader), options).await
+}
+
+/// The tokio version of [`recognize_response`](../fn.recognize_response.html).
+pub async fn recognize_response<R>(reader: R) -> Result<ResponseHead, Error>
+where
+  R: AsyncRead + Unpin,
+{
+  crate::recognize_response(Compat(reader)).await
+}
+
+/// The tokio version of [`recognize_response_with`](../fn.recognize_response_with.html).
+pub async fn recognize_response_with<R>(reader: R, options: &RecognizeOptions) -> Result<ResponseHead, Error>
+where
+  R: AsyncRead + Unpin,
+{
+  crate::recognize_response_with(Compat(reader), options).await
+}
+
+/// The tokio version of [`recognize_buffered`](../fn.recognize_buffered.html).
+pub async fn recognize_buffered<R>(reader: R) -> Result<Head, Error>
+where
+  R: AsyncBufRead + Unpin,
+{
+  crate::recognize_buffered(Compat(reader)).await
+}
+
+/// The tokio version of [`recognize_buffered_with`](../fn.recognize_buffered_with.html).
+pub async fn recognize_buffered_with<R>(reader: R, options: &RecognizeOptions) -> Result<Head, Error>
+where
+  R: AsyncBufRead + Unpin,
+{
+  crate::recognize_buffered_with(Compat(reader), options).await
+}
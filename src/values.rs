@@ -0,0 +1,272 @@
+use std::net::Ipv6Addr;
+
+use crate::error::Error;
+use crate::head::{is_token, OWS};
+use crate::target::split_port;
+
+/// The value of a `Host` header; the host as it was sent (IPv6 literals keep their brackets) and
+/// the port, when one was given.
+///
+/// ```rust
+/// use elaine::blocking::recognize;
+/// use elaine::Host;
+///
+/// let req: &[u8] = b"GET / HTTP/1.1\r\nHost: [::1]:8080\r\n\r\n";
+/// let head = recognize(req).unwrap();
+///
+/// assert_eq!(
+///   head.host().unwrap(),
+///   Some(Host {
+///     host: "[::1]".to_string(),
+///     port: Some(8080)
+///   })
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Host {
+  pub host: String,
+  pub port: Option<u16>,
+}
+
+impl Host {
+  /// Parses the value of a `Host` header. The host may be empty, as it is for requests to URIs
+  /// without an authority.
+  pub fn parse(value: &str) -> Result<Self, Error> {
+    let invalid = || Error::InvalidHeaderValue(HOST);
+    let (host, port) = split_port(value);
+
+    let port = match port {
+      Some(port) if !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) => {
+        Some(port.parse::<u16>().map_err(|_| invalid())?)
+      }
+      Some(_) => return Err(invalid()),
+      None => None,
+    };
+
+    let valid = match host.strip_prefix('[') {
+      Some(literal) => literal
+        .strip_suffix(']')
+        .is_some_and(|literal| literal.parse::<Ipv6Addr>().is_ok()),
+      // Registered names and IPv4 addresses; percent-encoded octets are left as they are.
+      None => host
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "-._~%!$&'()*+,;=".contains(c)),
+    };
+
+    match valid {
+      true => Ok(Host {
+        host: host.to_string(),
+        port,
+      }),
+      false => Err(invalid()),
+    }
+  }
+}
+
+impl std::fmt::Display for Host {
+  fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+    match self.port {
+      Some(port) => write!(formatter, "{}:{}", self.host, port),
+      None => write!(formatter, "{}", self.host),
+    }
+  }
+}
+
+/// The value of a `Content-Type` header. The type, subtype and parameter names are lowercased and
+/// parameter values are unquoted.
+///
+/// ```rust
+/// use elaine::blocking::recognize;
+///
+/// let req: &[u8] = b"POST / HTTP/1.1\r\nContent-Type: Multipart/Form-Data; boundary=\"a b\"\r\n\r\n";
+/// let media = recognize(req).unwrap().content_type().unwrap().unwrap();
+///
+/// assert_eq!(media.essence(), "multipart/form-data");
+/// assert_eq!(media.boundary(), Some("a b"));
+/// assert_eq!(media.charset(), None);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaType {
+  kind: String,
+  subtype: String,
+  params: Vec<(String, String)>,
+}
+
+impl MediaType {
+  /// Parses a media type, as sent in `Content-Type` or `Accept` headers.
+  pub fn parse(value: &str) -> Result<Self, Error> {
+    let invalid = || Error::InvalidHeaderValue(CONTENT_TYPE);
+    let (essence, mut rest) = value.split_at(value.find(';').unwrap_or(value.len()));
+
+    let (kind, subtype) = match essence.trim_matches(OWS).split_once('/') {
+      Some((kind, subtype)) if is_token(kind) && is_token(subtype) => (kind, subtype),
+      _ => return Err(invalid()),
+    };
+
+    let mut params = Vec::new();
+
+    while let Some(after) = rest.strip_prefix(';') {
+      let after = after.trim_start_matches(OWS);
+
+      // Empty parameters, as in `text/plain;;charset=utf-8`, are skipped.
+      if after.is_empty() || after.starts_with(';') {
+        rest = after;
+        continue;
+      }
+
+      let (name, after) = after.split_once('=').ok_or_else(invalid)?;
+
+      if !is_token(name) {
+        return Err(invalid());
+      }
+
+      let (value, after) = match after.strip_prefix('"') {
+        Some(quoted) => unquote(quoted).ok_or_else(invalid)?,
+        None => {
+          let end = after.find([';', ' ', '\t']).unwrap_or(after.len());
+          match &after[..end] {
+            token if is_token(token) => (token.to_string(), &after[end..]),
+            _ => return Err(invalid()),
+          }
+        }
+      };
+
+      params.push((name.to_ascii_lowercase(), value));
+      rest = after.trim_start_matches(OWS);
+
+      if !rest.is_empty() && !rest.starts_with(';') {
+        return Err(invalid());
+      }
+    }
+
+    Ok(MediaType {
+      kind: kind.to_ascii_lowercase(),
+      subtype: subtype.to_ascii_lowercase(),
+      params,
+    })
+  }
+
+  /// The top-level type, e.g. `text` for `text/html`.
+  pub fn kind(&self) -> &str {
+    &self.kind
+  }
+
+  pub fn subtype(&self) -> &str {
+    &self.subtype
+  }
+
+  /// The type and subtype without parameters, e.g. `text/html`.
+  pub fn essence(&self) -> String {
+    format!("{}/{}", self.kind, self.subtype)
+  }
+
+  /// The value of a parameter, ignoring the case of its name.
+  pub fn param(&self, name: &str) -> Option<&str> {
+    self
+      .params
+      .iter()
+      .find(|(key, _)| key.eq_ignore_ascii_case(name))
+      .map(|(_, value)| value.as_str())
+  }
+
+  pub fn params(&self) -> &[(String, String)] {
+    &self.params
+  }
+
+  pub fn charset(&self) -> Option<&str> {
+    self.param("charset")
+  }
+
+  pub fn boundary(&self) -> Option<&str> {
+    self.param("boundary")
+  }
+}
+
+impl std::fmt::Display for MediaType {
+  fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+    write!(formatter, "{}/{}", self.kind, self.subtype)?;
+
+    for (name, value) in &self.params {
+      match is_token(value) {
+        true => write!(formatter, "; {}={}", name, value)?,
+        false => write!(formatter, "; {}=\"{}\"", name, escape(value))?,
+      }
+    }
+
+    Ok(())
+  }
+}
+
+pub(crate) const HOST: &str = "Host";
+
+pub(crate) const CONTENT_TYPE: &str = "Content-Type";
+
+pub(crate) const CONNECTION: &str = "Connection";
+
+pub(crate) const USER_AGENT: &str = "User-Agent";
+
+pub(crate) const REFERER: &str = "Referer";
+
+pub(crate) const ORIGIN: &str = "Origin";
+
+/// Reads a quoted-string whose opening quote was already consumed, returning its unescaped contents
+/// and the input following the closing quote.
+pub(crate) fn unquote(input: &str) -> Option<(String, &str)> {
+  let mut value = String::new();
+  let mut chars = input.char_indices();
+
+  while let Some((index, c)) = chars.next() {
+    match c {
+      '"' => return Some((value, &input[index + 1..])),
+      '\\' => value.push(chars.next()?.1),
+      c => value.push(c),
+    }
+  }
+
+  None
+}
+
+/// Escapes a value so that it can be written as a quoted-string, without the quotes.
+pub(crate) fn escape(value: &str) -> String {
+  value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod test {
+  use super::{Host, MediaType};
+
+  #[test]
+  fn test_host() {
+    let host = Host::parse("example.com").unwrap();
+    assert_eq!((host.host.as_str(), host.port), ("example.com", None));
+    let host = Host::parse("127.0.0.1:80").unwrap();
+    assert_eq!((host.host.as_str(), host.port), ("127.0.0.1", Some(80)));
+    let host = Host::parse("[2001:db8::1]").unwrap();
+    assert_eq!((host.host.as_str(), host.port), ("[2001:db8::1]", None));
+    assert_eq!(Host::parse("[::1]:443").unwrap().to_string(), "[::1]:443");
+
+    assert!(Host::parse("example.com:http").is_err());
+    assert!(Host::parse("example.com:70000").is_err());
+    assert!(Host::parse("example.com:").is_err());
+    assert!(Host::parse("[::1").is_err());
+    assert!(Host::parse("[not-ipv6]").is_err());
+    assert!(Host::parse("exa mple.com").is_err());
+    assert!(Host::parse("user@example.com").is_err());
+  }
+
+  #[test]
+  fn test_media_type() {
+    let media = MediaType::parse("text/HTML ; Charset=\"utf-8\";;level=1").unwrap();
+    assert_eq!(media.kind(), "text");
+    assert_eq!(media.subtype(), "html");
+    assert_eq!(media.charset(), Some("utf-8"));
+    assert_eq!(media.param("LEVEL"), Some("1"));
+    assert_eq!(media.to_string(), "text/html; charset=utf-8; level=1");
+
+    assert!(MediaType::parse("text").is_err());
+    assert!(MediaType::parse("text/").is_err());
+    assert!(MediaType::parse("text/html; charset").is_err());
+    assert!(MediaType::parse("text/html; charset=\"utf-8").is_err());
+    assert!(MediaType::parse("text/html; charset=utf 8").is_err());
+  }
+}
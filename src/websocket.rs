@@ -0,0 +1,605 @@
+//! The server side of the [WebSocket opening handshake][handshake]. A request read with
+//! [`recognize`](../fn.recognize.html) leaves the stream positioned right after its head, so once
+//! [`accept`] has written the `101 Switching Protocols` response the stream carries WebSocket
+//! frames, which are read with a [`Reader`] and written with a [`Writer`].
+//!
+//! ```rust,no_run
+//! use async_std::net::TcpListener;
+//! use async_std::task::block_on;
+//! use elaine::{recognize, websocket};
+//!
+//! block_on(async {
+//!   let listener = TcpListener::bind("0.0.0.0:8080").await.unwrap();
+//!   let (mut stream, _) = listener.accept().await.unwrap();
+//!   let head = recognize(&mut stream).await.unwrap();
+//!
+//!   match websocket::accept(&head, &mut stream).await {
+//!     Ok(_) => println!("upgraded"),
+//!     Err(e) => println!("not a websocket handshake: {}", e),
+//!   }
+//! });
+//! ```
+//!
+//! [handshake]: https://tools.ietf.org/html/rfc6455#section-4.2
+//! [`accept`]: fn.accept.html
+//! [`Reader`]: struct.Reader.html
+//! [`Writer`]: struct.Writer.html
+use futures_io::{AsyncRead, AsyncWrite};
+use std::collections::hash_map::RandomState;
+use std::convert::TryFrom;
+use std::hash::BuildHasher;
+use std::io::ErrorKind;
+use std::marker::Unpin;
+use std::time::Instant;
+
+use crate::base64;
+use crate::error::{Error, Position};
+use crate::head::{is_token, Head, RequestMethod, RequestVersion, OWS};
+use crate::recognize::{read_some, write_all};
+use crate::sha1;
+use crate::values::{CONNECTION, HOST};
+
+/// The only version of the protocol, sent in `Sec-WebSocket-Version`.
+pub const VERSION: &str = "13";
+
+const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+const UPGRADE: &str = "Upgrade";
+
+const SEC_WEBSOCKET_KEY: &str = "Sec-WebSocket-Key";
+
+const SEC_WEBSOCKET_VERSION: &str = "Sec-WebSocket-Version";
+
+const SEC_WEBSOCKET_PROTOCOL: &str = "Sec-WebSocket-Protocol";
+
+/// Computes the `Sec-WebSocket-Accept` value answering a `Sec-WebSocket-Key`.
+///
+/// ```rust
+/// use elaine::websocket::accept_key;
+///
+/// assert_eq!(accept_key("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+/// ```
+pub fn accept_key(key: &str) -> String {
+  base64::encode(&sha1::digest(format!("{}{}", key, GUID).as_bytes()))
+}
+
+/// The subprotocols offered in the `Sec-WebSocket-Protocol` headers of a request, in order of the
+/// client's preference.
+pub fn protocols(head: &Head) -> Vec<&str> {
+  head
+    .headers()
+    .get_all(SEC_WEBSOCKET_PROTOCOL)
+    .flat_map(|value| value.split(','))
+    .map(|protocol| protocol.trim_matches(OWS))
+    .filter(|protocol| !protocol.is_empty())
+    .collect()
+}
+
+/// Checks that the request is a WebSocket opening handshake and writes the `101 Switching
+/// Protocols` response, returning the stream. No subprotocol is selected; see [`accept_with`].
+///
+/// When the request is not a valid handshake nothing is written and the error names the offending
+/// part: `Error::InvalidMethod` or `Error::InvalidVersion` for requests other than `GET` over
+/// HTTP/1.1, or `Error::InvalidHeaderValue` with the name of a missing or malformed header. A
+/// server should answer those with `400 Bad Request`, or with `426 Upgrade Required` and a
+/// `Sec-WebSocket-Version` header holding [`VERSION`] when that header was the problem.
+///
+/// [`accept_with`]: fn.accept_with.html
+/// [`VERSION`]: constant.VERSION.html
+pub async fn accept<S>(head: &Head, stream: S) -> Result<S, Error>
+where
+  S: AsyncWrite + Unpin,
+{
+  accept_with(head, stream, &[]).await.map(|(stream, _)| stream)
+}
+
+/// Like [`accept`], selecting the first subprotocol offered by the client that is also in
+/// `supported`. The selected subprotocol is sent in the response and returned with the stream;
+/// when there is none the header is left out, and the client decides whether to continue.
+///
+/// [`accept`]: fn.accept.html
+pub async fn accept_with<S>(head: &Head, mut stream: S, supported: &[&str]) -> Result<(S, Option<String>), Error>
+where
+  S: AsyncWrite + Unpin,
+{
+  let key = check(head)?;
+
+  let offered = protocols(head);
+  if !offered.iter().all(|protocol| is_token(protocol)) {
+    return Err(Error::InvalidHeaderValue(SEC_WEBSOCKET_PROTOCOL));
+  }
+
+  let selected = offered
+    .into_iter()
+    .find(|protocol| supported.contains(protocol))
+    .map(String::from);
+
+  let mut response = format!(
+    "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n",
+    accept_key(key)
+  );
+  if let Some(protocol) = &selected {
+    response.push_str(&format!("{}: {}\r\n", SEC_WEBSOCKET_PROTOCOL, protocol));
+  }
+  response.push_str("\r\n");
+
+  write_all(&mut stream, response.as_bytes()).await?;
+  Ok((stream, selected))
+}
+
+// Validates the handshake requirements of RFC 6455 §4.2.1, returning the key.
+fn check(head: &Head) -> Result<&str, Error> {
+  if head.method() != Some(RequestMethod::GET) {
+    return Err(Error::InvalidMethod(Position::default()));
+  }
+
+  if head.version() != Some(RequestVersion::RFC2616) {
+    return Err(Error::InvalidVersion(Position::default()));
+  }
+
+  if head.single(HOST)?.is_none() {
+    return Err(Error::InvalidHeaderValue(HOST));
+  }
+
+  let upgrade = head
+    .headers()
+    .get_all(UPGRADE)
+    .flat_map(|value| value.split(','))
+    .any(|protocol| protocol.trim_matches(OWS).eq_ignore_ascii_case("websocket"));
+
+  if !upgrade {
+    return Err(Error::InvalidHeaderValue(UPGRADE));
+  }
+
+  if !head.connection()?.iter().any(|option| option == "upgrade") {
+    return Err(Error::InvalidHeaderValue(CONNECTION));
+  }
+
+  if head.single(SEC_WEBSOCKET_VERSION)? != Some(VERSION) {
+    return Err(Error::InvalidHeaderValue(SEC_WEBSOCKET_VERSION));
+  }
+
+  // The key is a base64 encoded 16 byte nonce.
+  let key = head.single(SEC_WEBSOCKET_KEY)?.unwrap_or_default();
+  match base64::decode(key) {
+    Some(nonce) if nonce.len() == 16 => Ok(key),
+    _ => Err(Error::InvalidHeaderValue(SEC_WEBSOCKET_KEY)),
+  }
+}
+
+/// The role of this end of a connection, which decides the masking of frames; clients mask every
+/// frame they send and servers none.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Role {
+  Server,
+  Client,
+}
+
+/// The largest message a [`Reader`] accepts unless configured otherwise, 1 MiB.
+///
+/// [`Reader`]: struct.Reader.html
+pub const DEFAULT_MAX_MESSAGE: usize = 1 << 20;
+
+/// The close code of `Error::InvalidFrame` for frames that break the protocol.
+pub const PROTOCOL_ERROR: u16 = 1002;
+
+/// The close code of `Error::InvalidFrame` for text messages that are not utf-8.
+pub const INVALID_PAYLOAD: u16 = 1007;
+
+/// The close code of `Error::InvalidFrame` for messages over the maximum size.
+pub const MESSAGE_TOO_BIG: u16 = 1009;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Opcode {
+  Continuation,
+  Text,
+  Binary,
+  Close,
+  Ping,
+  Pong,
+}
+
+impl Opcode {
+  fn parse(bits: u8) -> Option<Self> {
+    match bits {
+      0x0 => Some(Opcode::Continuation),
+      0x1 => Some(Opcode::Text),
+      0x2 => Some(Opcode::Binary),
+      0x8 => Some(Opcode::Close),
+      0x9 => Some(Opcode::Ping),
+      0xA => Some(Opcode::Pong),
+      _ => None,
+    }
+  }
+
+  fn bits(self) -> u8 {
+    match self {
+      Opcode::Continuation => 0x0,
+      Opcode::Text => 0x1,
+      Opcode::Binary => 0x2,
+      Opcode::Close => 0x8,
+      Opcode::Ping => 0x9,
+      Opcode::Pong => 0xA,
+    }
+  }
+
+  /// Control frames may be sent between the fragments of a message, but cannot be fragmented
+  /// themselves.
+  pub fn is_control(self) -> bool {
+    matches!(self, Opcode::Close | Opcode::Ping | Opcode::Pong)
+  }
+}
+
+/// A single frame, with its payload unmasked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+  pub fin: bool,
+  pub opcode: Opcode,
+  pub payload: Vec<u8>,
+}
+
+/// The code and reason of a close frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Close {
+  pub code: u16,
+  pub reason: String,
+}
+
+impl Close {
+  // Codes that may appear in a close frame; 1004 is reserved, and 1005, 1006 and 1015 stand for the
+  // absence of a close frame.
+  fn is_valid_code(code: u16) -> bool {
+    matches!(code, 1000..=1003 | 1007..=1014 | 3000..=4999)
+  }
+}
+
+/// A complete message, reassembled from its fragments, or a control frame.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+  Text(String),
+  Binary(Vec<u8>),
+  Ping(Vec<u8>),
+  Pong(Vec<u8>),
+  /// The peer is closing the connection; it expects a close frame in response, after which the
+  /// stream should be closed. A close frame without a body has no `Close`.
+  Close(Option<Close>),
+}
+
+/// Reads the frames of an upgraded connection, reassembling fragmented messages. The reader
+/// enforces the framing rules of RFC 6455 §5, failing with an `Error::InvalidFrame` that holds the
+/// close code to send to the peer before closing the connection: [`PROTOCOL_ERROR`] for malformed
+/// frames, [`INVALID_PAYLOAD`] for text that is not utf-8 and [`MESSAGE_TOO_BIG`] for messages
+/// over the maximum size. No extensions are supported.
+///
+/// Pings are returned to the caller, who is expected to answer them with a pong through a
+/// [`Writer`].
+///
+/// ```rust
+/// use async_std::task::block_on;
+/// use elaine::websocket::{Message, Reader, Role};
+///
+/// block_on(async {
+///   // The masked "Hello" text frame of RFC 6455 §5.7.
+///   let mut stream: &[u8] = &[0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58];
+///
+///   let mut reader = Reader::new(&mut stream, Role::Server);
+///   assert_eq!(reader.read().await.unwrap(), Message::Text("Hello".to_string()));
+/// });
+/// ```
+///
+/// [`PROTOCOL_ERROR`]: constant.PROTOCOL_ERROR.html
+/// [`INVALID_PAYLOAD`]: constant.INVALID_PAYLOAD.html
+/// [`MESSAGE_TOO_BIG`]: constant.MESSAGE_TOO_BIG.html
+/// [`Writer`]: struct.Writer.html
+#[derive(Debug)]
+pub struct Reader<R> {
+  reader: R,
+  role: Role,
+  max_message: usize,
+  fragments: Option<(Opcode, Vec<u8>)>,
+}
+
+impl<R> Reader<R>
+where
+  R: AsyncRead + Unpin,
+{
+  pub fn new(reader: R, role: Role) -> Self {
+    Reader {
+      reader,
+      role,
+      max_message: DEFAULT_MAX_MESSAGE,
+      fragments: None,
+    }
+  }
+
+  /// The largest message, in bytes, that may be read; larger messages fail before their payload
+  /// is read.
+  pub fn max_message(self, max_message: usize) -> Self {
+    Reader { max_message, ..self }
+  }
+
+  /// Reads the next message, or control frame.
+  pub async fn read(&mut self) -> Result<Message, Error> {
+    loop {
+      let Frame { fin, opcode, payload } = self.read_frame().await?;
+
+      let (opcode, payload) = match (opcode, self.fragments.take()) {
+        (Opcode::Ping, fragments) => {
+          self.fragments = fragments;
+          return Ok(Message::Ping(payload));
+        }
+        (Opcode::Pong, fragments) => {
+          self.fragments = fragments;
+          return Ok(Message::Pong(payload));
+        }
+        (Opcode::Close, _) => return close(&payload).map(Message::Close),
+        (Opcode::Continuation, Some((opcode, mut buffered))) => {
+          buffered.extend_from_slice(&payload);
+          (opcode, buffered)
+        }
+        (Opcode::Text | Opcode::Binary, None) => (opcode, payload),
+        // A continuation without a message to continue, or a new message in the middle of one.
+        _ => return Err(Error::InvalidFrame(PROTOCOL_ERROR)),
+      };
+
+      if !fin {
+        self.fragments = Some((opcode, payload));
+        continue;
+      }
+
+      return match opcode {
+        Opcode::Text => String::from_utf8(payload)
+          .map(Message::Text)
+          .map_err(|_| Error::InvalidFrame(INVALID_PAYLOAD)),
+        _ => Ok(Message::Binary(payload)),
+      };
+    }
+  }
+
+  /// Reads a single frame, without reassembling fragments; frames read this way are not added to a
+  /// message that [`read`] is in the middle of.
+  ///
+  /// [`read`]: #method.read
+  pub async fn read_frame(&mut self) -> Result<Frame, Error> {
+    let mut header = [0u8; 2];
+    read_exact(&mut self.reader, &mut header).await?;
+
+    let fin = header[0] & 0x80 != 0;
+    let opcode = match Opcode::parse(header[0] & 0x0F) {
+      // The reserved bits are only set by extensions.
+      Some(opcode) if header[0] & 0x70 == 0 => opcode,
+      _ => return Err(Error::InvalidFrame(PROTOCOL_ERROR)),
+    };
+    let masked = header[1] & 0x80 != 0;
+
+    if masked != (self.role == Role::Server) {
+      return Err(Error::InvalidFrame(PROTOCOL_ERROR));
+    }
+
+    let length = match header[1] & 0x7F {
+      126 => {
+        let mut extended = [0u8; 2];
+        read_exact(&mut self.reader, &mut extended).await?;
+        u64::from(u16::from_be_bytes(extended))
+      }
+      127 => {
+        let mut extended = [0u8; 8];
+        read_exact(&mut self.reader, &mut extended).await?;
+        match u64::from_be_bytes(extended) {
+          length if length >> 63 == 0 => length,
+          _ => return Err(Error::InvalidFrame(PROTOCOL_ERROR)),
+        }
+      }
+      length => u64::from(length),
+    };
+
+    if opcode.is_control() && (!fin || length > 125) {
+      return Err(Error::InvalidFrame(PROTOCOL_ERROR));
+    }
+
+    let buffered = self.fragments.as_ref().map_or(0, |(_, payload)| payload.len());
+    let length = match usize::try_from(length) {
+      Ok(length) if opcode.is_control() || length <= self.max_message.saturating_sub(buffered) => length,
+      _ => return Err(Error::InvalidFrame(MESSAGE_TOO_BIG)),
+    };
+
+    let mut key = [0u8; 4];
+    if masked {
+      read_exact(&mut self.reader, &mut key).await?;
+    }
+
+    let mut payload = vec![0u8; length];
+    read_exact(&mut self.reader, &mut payload).await?;
+    apply_mask(&mut payload, key);
+
+    Ok(Frame { fin, opcode, payload })
+  }
+
+  pub fn into_inner(self) -> R {
+    self.reader
+  }
+}
+
+/// Writes frames to an upgraded connection, masking them when writing as a [`Role::Client`].
+///
+/// ```rust
+/// use async_std::task::block_on;
+/// use elaine::websocket::{Message, Role, Writer};
+///
+/// block_on(async {
+///   let mut writer = Writer::new(Vec::new(), Role::Server);
+///   writer.send(&Message::Text("Hello".to_string())).await.unwrap();
+///   assert_eq!(writer.into_inner(), b"\x81\x05Hello");
+/// });
+/// ```
+///
+/// [`Role::Client`]: enum.Role.html#variant.Client
+#[derive(Debug)]
+pub struct Writer<W> {
+  writer: W,
+  role: Role,
+  fragment: Option<usize>,
+}
+
+impl<W> Writer<W>
+where
+  W: AsyncWrite + Unpin,
+{
+  pub fn new(writer: W, role: Role) -> Self {
+    Writer {
+      writer,
+      role,
+      fragment: None,
+    }
+  }
+
+  /// Splits text and binary messages into frames carrying at most this many bytes each.
+  pub fn fragment(self, size: usize) -> Self {
+    Writer {
+      fragment: Some(size.max(1)),
+      ..self
+    }
+  }
+
+  pub async fn send(&mut self, message: &Message) -> Result<(), Error> {
+    let (opcode, payload) = match message {
+      Message::Text(text) => (Opcode::Text, text.as_bytes().to_vec()),
+      Message::Binary(data) => (Opcode::Binary, data.clone()),
+      Message::Ping(data) => (Opcode::Ping, data.clone()),
+      Message::Pong(data) => (Opcode::Pong, data.clone()),
+      Message::Close(None) => (Opcode::Close, Vec::new()),
+      Message::Close(Some(Close { code, reason })) => {
+        let mut payload = code.to_be_bytes().to_vec();
+        payload.extend_from_slice(reason.as_bytes());
+        (Opcode::Close, payload)
+      }
+    };
+
+    let size = match (opcode.is_control(), self.fragment) {
+      (false, Some(size)) => size,
+      _ => payload.len().max(1),
+    };
+    let chunks = payload.chunks(size).collect::<Vec<_>>();
+    let last = chunks.len().saturating_sub(1);
+
+    if chunks.is_empty() {
+      return self
+        .write_frame(&Frame {
+          fin: true,
+          opcode,
+          payload,
+        })
+        .await;
+    }
+
+    for (index, chunk) in chunks.into_iter().enumerate() {
+      let frame = Frame {
+        fin: index == last,
+        opcode: if index == 0 { opcode } else { Opcode::Continuation },
+        payload: chunk.to_vec(),
+      };
+      self.write_frame(&frame).await?;
+    }
+
+    Ok(())
+  }
+
+  /// Writes a single frame as given, which allows sending fragments as they become available.
+  /// Control frames must be final and carry no more than 125 bytes, and close frames a code and
+  /// reason the peer would accept, otherwise nothing is written and an
+  /// `std::io::ErrorKind::InvalidInput` error is returned.
+  pub async fn write_frame(&mut self, frame: &Frame) -> Result<(), Error> {
+    let length = frame.payload.len();
+    let invalid_close = frame.opcode == Opcode::Close && close(&frame.payload).is_err();
+
+    if (frame.opcode.is_control() && (!frame.fin || length > 125)) || invalid_close {
+      return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid control frame").into());
+    }
+
+    let mut bytes = Vec::with_capacity(length + 14);
+    bytes.push(u8::from(frame.fin) << 7 | frame.opcode.bits());
+
+    let mask = u8::from(self.role == Role::Client) << 7;
+    match length {
+      0..=125 => bytes.push(mask | length as u8),
+      126..=0xFFFF => {
+        bytes.push(mask | 126);
+        bytes.extend_from_slice(&(length as u16).to_be_bytes());
+      }
+      _ => {
+        bytes.push(mask | 127);
+        bytes.extend_from_slice(&(length as u64).to_be_bytes());
+      }
+    }
+
+    match self.role {
+      Role::Client => {
+        let key = masking_key();
+        bytes.extend_from_slice(&key);
+        let start = bytes.len();
+        bytes.extend_from_slice(&frame.payload);
+        apply_mask(&mut bytes[start..], key);
+      }
+      Role::Server => bytes.extend_from_slice(&frame.payload),
+    }
+
+    write_all(&mut self.writer, &bytes).await
+  }
+
+  pub fn into_inner(self) -> W {
+    self.writer
+  }
+}
+
+// Parses the body of a close frame; a code, optionally followed by a utf-8 reason.
+fn close(payload: &[u8]) -> Result<Option<Close>, Error> {
+  match payload.len() {
+    0 => Ok(None),
+    1 => Err(Error::InvalidFrame(PROTOCOL_ERROR)),
+    _ => {
+      let code = u16::from_be_bytes([payload[0], payload[1]]);
+
+      if !Close::is_valid_code(code) {
+        return Err(Error::InvalidFrame(PROTOCOL_ERROR));
+      }
+
+      match String::from_utf8(payload[2..].to_vec()) {
+        Ok(reason) => Ok(Some(Close { code, reason })),
+        Err(_) => Err(Error::InvalidFrame(INVALID_PAYLOAD)),
+      }
+    }
+  }
+}
+
+fn apply_mask(payload: &mut [u8], key: [u8; 4]) {
+  for (index, byte) in payload.iter_mut().enumerate() {
+    *byte ^= key[index % 4];
+  }
+}
+
+// Masking keys only need to be unpredictable to scripts in the browser, so the random keys of the
+// standard library's hasher are enough; each `RandomState` hashes differently.
+fn masking_key() -> [u8; 4] {
+  let hash = RandomState::new().hash_one(Instant::now());
+  (hash as u32).to_be_bytes()
+}
+
+async fn read_exact<R>(reader: &mut R, mut buf: &mut [u8]) -> Result<(), Error>
+where
+  R: AsyncRead + Unpin,
+{
+  while !buf.is_empty() {
+    match read_some(reader, buf).await? {
+      0 => {
+        let eof = std::io::Error::new(
+          ErrorKind::UnexpectedEof,
+          "Reader exhausted before end of WebSocket frame",
+        );
+        return Err(eof.into());
+      }
+      amount => buf = &mut buf[amount..],
+    }
+  }
+
+  Ok(())
+}
@@ -0,0 +1,185 @@
+#![cfg(test)]
+
+mod helpers;
+
+use async_std::io::BufReader;
+use async_std::prelude::*;
+use async_std::task::block_on;
+use elaine::{blocking, recognize, recognize_buffered, Connection, Error, Head, Parser, RequestMethod, Status};
+use helpers::{AsyncBuffer, Trickle};
+
+const VALID: &str = "POST /backends HTTP/1.1\r\nComplex: aa𐆒\r\nContent-Length: 3\r\n\r\nhey";
+const INVALID: &str = "GET / HTTP/1.0\r\nbad-line";
+
+// Runs the input through every enabled backend, returning the result and the unread bytes.
+fn each_backend(input: &str) -> Vec<(&'static str, Result<Head, Error>, String)> {
+  let mut results = Vec::new();
+
+  let mut buffer = AsyncBuffer::new(input);
+  let result = block_on(async { recognize(&mut buffer).await });
+  results.push(("async-std/futures-io", result, format!("{}", buffer)));
+
+  let mut buffer = AsyncBuffer::new(input);
+  let result = blocking::recognize(&mut buffer);
+  results.push(("blocking", result, format!("{}", buffer)));
+
+  let mut trickle = Trickle::new(input);
+  let result = block_on(async { recognize(&mut trickle).await });
+  let mut rest = String::new();
+  block_on(async { trickle.read_to_string(&mut rest).await }).unwrap();
+  results.push(("trickle", result, format!("{}", AsyncBuffer::new(rest))));
+
+  let mut reader = BufReader::new(AsyncBuffer::new(input));
+  let result = block_on(async { recognize_buffered(&mut reader).await });
+  let mut rest = String::new();
+  block_on(async { reader.read_to_string(&mut rest).await }).unwrap();
+  results.push(("buffered", result, format!("{}", AsyncBuffer::new(rest))));
+
+  let mut reader = std::io::BufReader::new(input.as_bytes());
+  let result = blocking::recognize_buffered(&mut reader);
+  let mut rest = String::new();
+  std::io::Read::read_to_string(&mut reader, &mut rest).unwrap();
+  results.push(("blocking-buffered", result, format!("{}", AsyncBuffer::new(rest))));
+
+  let mut parser = Parser::new();
+  let (result, rest) = match parser.feed(input.as_bytes()) {
+    Status::Complete(head, consumed) => (Ok(head), &input[consumed..]),
+    Status::Error(e) => (Err(e), ""),
+    Status::NeedMore => match parser.eof() {
+      Status::Complete(head, _) => (Ok(head), ""),
+      Status::Error(e) => (Err(e), ""),
+      Status::NeedMore => panic!("parser needs more after the end of its input"),
+    },
+  };
+  results.push(("parser", result, format!("{}", AsyncBuffer::new(rest))));
+
+  #[cfg(feature = "tokio")]
+  {
+    let mut buffer = AsyncBuffer::new(input);
+    let result = block_on(async { elaine::tokio::recognize(&mut buffer).await });
+    results.push(("tokio", result, format!("{}", buffer)));
+  }
+
+  results
+}
+
+#[test]
+fn backends_valid() {
+  for (backend, result, rest) in each_backend(VALID) {
+    let head = result.unwrap_or_else(|e| panic!("{} failed: {:?}", backend, e));
+    assert_eq!(head.method(), Some(RequestMethod::POST), "{}", backend);
+    assert_eq!(head.find_header("complex"), Some("aa𐆒".to_string()), "{}", backend);
+    assert_eq!(head.len(), Some(3), "{}", backend);
+    assert_eq!(rest, format!("{}", AsyncBuffer::new("hey")), "{}", backend);
+  }
+}
+
+#[test]
+fn backends_invalid() {
+  for (backend, result, _) in each_backend(INVALID) {
+    assert!(matches!(result, Err(Error::UnexpectedEof(_))), "{}", backend);
+  }
+}
+
+// Characters of two, three and four bytes directly before a line break, and before the end of the
+// head, must not hide the CR LF that follows them.
+#[test]
+fn backends_multibyte_before_crlf() {
+  for c in &["é", "€", "😀"] {
+    let inputs = vec![
+      (format!("GET /a HTTP/1.1\r\nX:{}\r\nHost: x\r\n\r\n", c), c.to_string()),
+      (format!("GET /a HTTP/1.1\r\nHost: x\r\nX:{}\r\n\r\n", c), c.to_string()),
+      (
+        format!("GET /a HTTP/1.1\r\nHost: x\r\nX: a{}\r\n\r\n", c),
+        format!("a{}", c),
+      ),
+      (
+        format!("GET /a HTTP/1.1\r\nHost: x\r\nX: ab{}\r\n\r\n", c),
+        format!("ab{}", c),
+      ),
+    ];
+
+    for (head, value) in inputs {
+      let input = format!("{}GET /b HTTP/1.1\r\n\r\n", head);
+
+      for (backend, result, rest) in each_backend(&input) {
+        let parsed = result.unwrap_or_else(|e| panic!("{} failed on {:?}: {:?}", backend, input, e));
+        assert_eq!(parsed.path(), Some("/a".to_string()), "{}", backend);
+        assert_eq!(parsed.find_header("x"), Some(value.clone()), "{}", backend);
+        assert_eq!(parsed.find_header("host"), Some("x".to_string()), "{}", backend);
+        assert_eq!(parsed.headers().len(), 2, "{}", backend);
+        assert!(parsed.diagnostics().is_empty(), "{}", backend);
+        assert_eq!(
+          rest,
+          format!("{}", AsyncBuffer::new("GET /b HTTP/1.1\r\n\r\n")),
+          "{}",
+          backend
+        );
+      }
+
+      let requests = format!("{}{}GET /c HTTP/1.1\r\n\r\n", input, head.replace("/a", "/b"));
+      let mut connection = Connection::new(AsyncBuffer::new(requests));
+      let paths = block_on(async {
+        let mut paths = Vec::new();
+        while let Some(result) = connection.next().await {
+          paths.push(result.unwrap().0.path().unwrap());
+        }
+        paths
+      });
+      assert_eq!(paths, vec!["/a", "/b", "/b", "/c"]);
+    }
+  }
+}
+
+// A CR that is not followed by a LF is part of a line, for buffered and unbuffered readers alike.
+#[test]
+fn backends_bare_cr() {
+  let input = "GET /a HTTP/1.1\r\nHost: x\r\n\r\r\n\r\nGET /b HTTP/1.1\r\n\r\n";
+  let results = each_backend(input);
+  let (_, expected, _) = &results[0];
+  let expected = expected
+    .as_ref()
+    .map(|head| head.to_string())
+    .map_err(|e| e.to_string());
+
+  for (backend, result, rest) in &results {
+    let result = result.as_ref().map(|head| head.to_string()).map_err(|e| e.to_string());
+    assert_eq!(result, expected, "{}", backend);
+    if result.is_ok() {
+      assert_eq!(
+        rest,
+        &format!("{}", AsyncBuffer::new("GET /b HTTP/1.1\r\n\r\n")),
+        "{}",
+        backend
+      );
+    }
+  }
+}
+
+// Empty lines before the start line are skipped by every backend, and never end a head by
+// themselves.
+#[test]
+fn backends_leading_empty_lines() {
+  for input in &[
+    "\r\nGET /a HTTP/1.1\r\n\r\nrest",
+    "\r\n\r\n\r\nGET /a HTTP/1.1\r\nHost: x\r\n\r\nrest",
+  ] {
+    for (backend, result, rest) in each_backend(input) {
+      let head = result.unwrap_or_else(|e| panic!("{} failed on {:?}: {:?}", backend, input, e));
+      assert_eq!(head.path(), Some("/a".to_string()), "{}", backend);
+      assert_eq!(rest, format!("{}", AsyncBuffer::new("rest")), "{}", backend);
+    }
+  }
+
+  for input in &["\r\n", "\r\n\r\n", "\r\n\r\n\r\n"] {
+    for (backend, result, _) in each_backend(input) {
+      assert!(
+        matches!(result, Err(Error::UnexpectedEof(_))),
+        "{} on {:?}: {:?}",
+        backend,
+        input,
+        result
+      );
+    }
+  }
+}
@@ -0,0 +1,73 @@
+#![cfg(test)]
+
+use elaine::{blocking, Error, RequestMethod};
+use std::io::{BufReader, Read};
+
+// Hands out a single byte per read, making over-reads visible.
+struct Trickle<'a>(&'a [u8]);
+
+impl<'a> Read for Trickle<'a> {
+  fn read(&mut self, dest: &mut [u8]) -> std::io::Result<usize> {
+    match (self.0.split_first(), dest.first_mut()) {
+      (Some((first, rest)), Some(slot)) => {
+        *slot = *first;
+        self.0 = rest;
+        Ok(1)
+      }
+      _ => Ok(0),
+    }
+  }
+}
+
+#[test]
+fn blocking_and_read_after() {
+  let mut req: &[u8] = b"POST /create HTTP/1.1\r\nContent-Length: 3\r\n\r\nhey";
+  let head = blocking::recognize(&mut req).unwrap();
+  assert_eq!(head.method(), Some(RequestMethod::POST));
+  assert_eq!(head.len(), Some(3));
+  assert_eq!(req, b"hey");
+}
+
+#[test]
+fn blocking_trickle() {
+  let mut reader = Trickle("GET /ɒ HTTP/1.1\r\nComplex: aa𐆒\r\n\r\nnext".as_bytes());
+  let head = blocking::recognize(&mut reader).unwrap();
+  assert_eq!(head.path(), Some("/ɒ".to_string()));
+  assert_eq!(head.find_header("complex"), Some("aa𐆒".to_string()));
+  assert_eq!(reader.0, b"next");
+}
+
+#[test]
+fn blocking_utf8_boundary() {
+  let mut req: &[u8] = "GET /first HTTP/1.1\r\nComplex: aaaࡘ\r\n\r\n".as_bytes();
+  let head = blocking::recognize(&mut req).unwrap();
+  assert_eq!(head.find_header("Complex"), Some("aaaࡘ".to_string()));
+}
+
+#[test]
+fn blocking_response() {
+  let mut res: &[u8] = b"HTTP/1.1 201 Created\r\nLocation: /things/1\r\n\r\n";
+  let head = blocking::recognize_response(&mut res).unwrap();
+  assert_eq!(head.status(), Some(201));
+  assert_eq!(head.find_header("location"), Some("/things/1".to_string()));
+}
+
+#[test]
+fn blocking_exhausted() {
+  let mut req: &[u8] = b"GET / HTTP/1.0\r\nbad-line";
+  let result = blocking::recognize(&mut req);
+  assert!(matches!(result, Err(Error::UnexpectedEof(_))));
+}
+
+#[test]
+fn blocking_buffered() {
+  for capacity in 1..8 {
+    let source: &[u8] = b"GET /small HTTP/1.1\r\nHost: example.com\r\n\r\nnext";
+    let mut reader = BufReader::with_capacity(capacity, source);
+    let head = blocking::recognize_buffered(&mut reader).unwrap();
+    assert_eq!(head.find_header("host"), Some("example.com".to_string()));
+    let mut rest = String::new();
+    reader.read_to_string(&mut rest).unwrap();
+    assert_eq!(rest, "next");
+  }
+}
@@ -0,0 +1,213 @@
+#![cfg(test)]
+
+mod helpers;
+
+use async_std::prelude::*;
+use async_std::task::block_on;
+use elaine::{recognize, recognize_response, Body, Error};
+use helpers::AsyncBuffer;
+use std::io::ErrorKind;
+
+#[test]
+fn body_content_length() {
+  let mut buffer = AsyncBuffer::new("POST / HTTP/1.1\r\nContent-Length: 3\r\n\r\nheyGET");
+  let result = block_on(async {
+    let head = recognize(&mut buffer).await?;
+    let mut body = Body::new(&head, &mut buffer)?;
+    let mut content = String::new();
+    body.read_to_string(&mut content).await?;
+    Ok::<String, std::io::Error>(content)
+  });
+  assert_eq!(result.unwrap(), "hey");
+  assert_eq!(format!("{}", buffer), format!("{}", AsyncBuffer::new("GET")));
+}
+
+#[test]
+fn body_without_length() {
+  let mut buffer = AsyncBuffer::new("GET / HTTP/1.1\r\n\r\nGET");
+  let result = block_on(async {
+    let head = recognize(&mut buffer).await?;
+    let mut body = Body::new(&head, &mut buffer)?;
+    let mut content = Vec::new();
+    body.read_to_end(&mut content).await?;
+    Ok::<Vec<u8>, std::io::Error>(content)
+  });
+  assert!(result.unwrap().is_empty());
+  assert_eq!(format!("{}", buffer), format!("{}", AsyncBuffer::new("GET")));
+}
+
+#[test]
+fn body_chunked() {
+  let mut buffer = AsyncBuffer::new(
+    "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n4;name=value\r\nWiki\r\n5\r\npedia\r\nE\r\n in\r\n\r\nchunks.\r\n0\r\nExpires: never\r\n\r\nGET",
+  );
+  let result = block_on(async {
+    let head = recognize(&mut buffer).await?;
+    let mut body = Body::new(&head, &mut buffer)?;
+    let mut content = String::new();
+    body.read_to_string(&mut content).await?;
+    assert!(body.is_done());
+    assert_eq!(body.trailers(), &[("Expires".to_string(), "never".to_string())][..]);
+    Ok::<String, std::io::Error>(content)
+  });
+  assert_eq!(result.unwrap(), "Wikipedia in\r\n\r\nchunks.");
+  assert_eq!(format!("{}", buffer), format!("{}", AsyncBuffer::new("GET")));
+}
+
+#[test]
+fn body_chunked_max_size() {
+  let mut buffer =
+    AsyncBuffer::new("POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n4\r\nWiki\r\n5\r\npedia\r\n0\r\n\r\n");
+  let result = block_on(async {
+    let head = recognize(&mut buffer).await?;
+    let mut body = Body::new(&head, &mut buffer)?.max_size(6);
+    let mut content = Vec::new();
+    body.read_to_end(&mut content).await
+  });
+  assert_eq!(result.unwrap_err().kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn body_chunked_trailer_limits() {
+  let read = |trailers: String, max: Option<usize>| {
+    let mut buffer = AsyncBuffer::new(format!(
+      "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n4\r\nWiki\r\n0\r\n{}\r\n",
+      trailers
+    ));
+    block_on(async {
+      let head = recognize(&mut buffer).await?;
+      let body = Body::new(&head, &mut buffer)?;
+      let mut body = match max {
+        Some(max) => body.max_size(max),
+        None => body,
+      };
+      let mut content = Vec::new();
+      body.read_to_end(&mut content).await?;
+      Ok::<usize, Error>(body.trailers().len())
+    })
+  };
+
+  let many = "X: y\r\n".repeat(20_000);
+  assert!(matches!(read(many.clone(), Some(10)), Err(Error::BodyTooLarge)));
+  assert!(matches!(read(many, None), Err(Error::InvalidChunk)));
+  assert_eq!(read("X: y\r\n".repeat(64), None).unwrap(), 64);
+  assert_eq!(read("X: y\r\n".to_string(), Some(8)).unwrap(), 1);
+}
+
+#[test]
+fn body_chunked_invalid_trailers() {
+  let read = |trailer: &str| {
+    let mut buffer = AsyncBuffer::new(format!(
+      "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n{}\r\n\r\n",
+      trailer
+    ));
+    block_on(async {
+      let head = recognize(&mut buffer).await?;
+      let mut body = Body::new(&head, &mut buffer)?;
+      let mut content = Vec::new();
+      body.read_to_end(&mut content).await?;
+      Ok::<Vec<(String, String)>, Error>(body.trailers().to_vec())
+    })
+  };
+
+  for trailer in [
+    "X Y: a",
+    ": a",
+    "X\x01: a",
+    "X: a\x00b",
+    "X: a\rb",
+    "X: a\x7f",
+    "no-colon",
+  ]
+  .iter()
+  {
+    assert!(matches!(read(trailer), Err(Error::InvalidChunk)), "{:?}", trailer);
+  }
+  assert_eq!(read("X:\ta\tb ").unwrap(), vec![("X".to_string(), "a\tb".to_string())]);
+}
+
+#[test]
+fn body_length_max_size() {
+  let mut buffer = AsyncBuffer::new("POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\n0123456789");
+  let result = block_on(async {
+    let head = recognize(&mut buffer).await?;
+    let mut body = Body::new(&head, &mut buffer)?.max_size(5);
+    let mut content = Vec::new();
+    body.read_to_end(&mut content).await
+  });
+  assert_eq!(result.unwrap_err().kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn body_chunked_invalid_size() {
+  let mut buffer = AsyncBuffer::new("POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\nzz\r\nWiki\r\n0\r\n\r\n");
+  let result = block_on(async {
+    let head = recognize(&mut buffer).await?;
+    let mut body = Body::new(&head, &mut buffer)?;
+    let mut content = Vec::new();
+    body.read_to_end(&mut content).await
+  });
+  assert_eq!(result.unwrap_err().kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn body_chunked_truncated() {
+  let mut buffer = AsyncBuffer::new("POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n4\r\nWi");
+  let result = block_on(async {
+    let head = recognize(&mut buffer).await?;
+    let mut body = Body::new(&head, &mut buffer)?;
+    let mut content = Vec::new();
+    body.read_to_end(&mut content).await
+  });
+  assert_eq!(result.unwrap_err().kind(), ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn body_unsupported_encoding() {
+  let mut buffer = AsyncBuffer::new("POST / HTTP/1.1\r\nTransfer-Encoding: gzip\r\n\r\n");
+  let result = block_on(async {
+    let head = recognize(&mut buffer).await?;
+    Body::new(&head, &mut buffer).map(|_| ())
+  });
+  assert!(result.is_err());
+}
+
+#[test]
+fn body_response_close_delimited() {
+  let mut buffer = AsyncBuffer::new("HTTP/1.1 200 OK\r\nTransfer-Encoding: gzip\r\n\r\nuntil the end");
+  let result = block_on(async {
+    let head = recognize_response(&mut buffer).await?;
+    let mut body = Body::response(&head, &mut buffer);
+    let mut content = String::new();
+    body.read_to_string(&mut content).await?;
+    assert!(body.is_done());
+    Ok::<String, std::io::Error>(content)
+  });
+  assert_eq!(result.unwrap(), "until the end");
+
+  let mut buffer = AsyncBuffer::new("HTTP/1.0 200 OK\r\n\r\nuntil the end");
+  let result = block_on(async {
+    let head = recognize_response(&mut buffer).await?;
+    let mut content = Vec::new();
+    Body::response(&head, &mut buffer)
+      .max_size(5)
+      .read_to_end(&mut content)
+      .await
+  });
+  let error = result.unwrap_err();
+  assert!(matches!(Error::from(error), Error::BodyTooLarge));
+}
+
+#[test]
+fn body_response_length() {
+  let mut buffer = AsyncBuffer::new("HTTP/1.1 204 No Content\r\n\r\nHTTP/1.1 200 OK\r\nContent-Length: 3\r\n\r\nhey");
+  let result = block_on(async {
+    let head = recognize_response(&mut buffer).await?;
+    assert!(Body::response(&head, &mut buffer).is_done());
+    let head = recognize_response(&mut buffer).await?;
+    let mut content = String::new();
+    Body::response(&head, &mut buffer).read_to_string(&mut content).await?;
+    Ok::<String, std::io::Error>(content)
+  });
+  assert_eq!(result.unwrap(), "hey");
+}
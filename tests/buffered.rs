@@ -0,0 +1,86 @@
+#![cfg(test)]
+
+mod helpers;
+
+use async_std::io::BufReader;
+use async_std::prelude::*;
+use async_std::task::block_on;
+use elaine::{recognize, recognize_buffered, recognize_buffered_with, Error, LimitExceeded, Limits, RecognizeOptions};
+use helpers::AsyncBuffer;
+
+#[test]
+fn buffered_and_read_after() {
+  let mut reader = BufReader::new(AsyncBuffer::new(
+    "POST /create HTTP/1.1\r\nContent-Length: 3\r\n\r\nhey",
+  ));
+  let head = block_on(async { recognize_buffered(&mut reader).await }).unwrap();
+  assert_eq!(head.len(), Some(3));
+  let mut rest = String::new();
+  block_on(async { reader.read_to_string(&mut rest).await }).unwrap();
+  assert_eq!(rest, "hey");
+}
+
+#[test]
+fn buffered_small_capacity() {
+  for capacity in 1..8 {
+    let source = AsyncBuffer::new("GET /small HTTP/1.1\r\nHost: example.com\r\n\r\nnext");
+    let mut reader = BufReader::with_capacity(capacity, source);
+    let head = block_on(async { recognize_buffered(&mut reader).await }).unwrap();
+    assert_eq!(head.path(), Some("/small".to_string()));
+    assert_eq!(head.find_header("host"), Some("example.com".to_string()));
+    let mut rest = String::new();
+    block_on(async { reader.read_to_string(&mut rest).await }).unwrap();
+    assert_eq!(rest, "next");
+  }
+}
+
+#[test]
+fn buffered_matches_recognize() {
+  let source = "GET /hello HTTP/1.0\r\nComplex: aaࡘ\r\nContent-Length: 10\r\n\r\n";
+  let mut reader = BufReader::new(AsyncBuffer::new(source));
+  let buffered = block_on(async { recognize_buffered(&mut reader).await }).unwrap();
+  let mut buffer = AsyncBuffer::new(source);
+  let unbuffered = block_on(async { recognize(&mut buffer).await }).unwrap();
+  assert_eq!(buffered.method(), unbuffered.method());
+  assert_eq!(buffered.path(), unbuffered.path());
+  assert_eq!(buffered.version(), unbuffered.version());
+  assert_eq!(buffered.len(), unbuffered.len());
+  assert_eq!(buffered.find_header("complex"), unbuffered.find_header("complex"));
+}
+
+#[test]
+fn buffered_exhausted() {
+  let mut reader = BufReader::new(AsyncBuffer::new("GET / HTTP/1.0\r\nbad-line"));
+  let result = block_on(async { recognize_buffered(&mut reader).await });
+  assert!(matches!(result, Err(Error::UnexpectedEof(_))));
+}
+
+#[test]
+fn buffered_invalid_request_line() {
+  let mut reader = BufReader::new(AsyncBuffer::new("a\r\n\r\n"));
+  let result = block_on(async { recognize_buffered(&mut reader).await });
+  assert!(matches!(result, Err(Error::InvalidRequestLine(_))));
+}
+
+#[test]
+fn buffered_head_limit() {
+  let options = RecognizeOptions {
+    limits: Limits {
+      head: 32,
+      ..Limits::default()
+    },
+    ..RecognizeOptions::default()
+  };
+  let mut reader = BufReader::new(AsyncBuffer::new(
+    "GET / HTTP/1.1\r\nA: 1\r\nB: 2\r\nC: 3\r\nD: 4\r\n\r\n",
+  ));
+  let result = block_on(async { recognize_buffered_with(&mut reader, &options).await });
+  assert!(matches!(result, Err(Error::LimitExceeded(LimitExceeded::HeadSize, _))));
+}
+
+#[test]
+fn buffered_invalid_framing() {
+  let mut reader = BufReader::new(AsyncBuffer::new("POST / HTTP/1.1\r\nTransfer-Encoding: gzip\r\n\r\n"));
+  let result = block_on(async { recognize_buffered(&mut reader).await });
+  assert!(matches!(result, Err(Error::InvalidFraming(_))), "{:?}", result);
+}
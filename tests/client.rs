@@ -0,0 +1,132 @@
+#![cfg(feature = "client")]
+
+use async_std::io::{ReadExt, WriteExt};
+use async_std::net::{TcpListener, TcpStream};
+use async_std::task::{block_on, spawn, JoinHandle};
+use elaine::{client, recognize, Body, Builder, Error, Head};
+
+fn request(lines: &[&str]) -> Head {
+  lines
+    .iter()
+    .try_fold(Builder::new(), |builder, line| builder.insert(line.to_string()))
+    .unwrap()
+    .collect::<Head>()
+}
+
+// Accepts a single connection, answering each request it reads with the next canned reply and
+// returning the request bodies.
+async fn stand_in(replies: &'static [&'static str]) -> (TcpStream, JoinHandle<Vec<String>>) {
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let addr = listener.local_addr().unwrap();
+
+  let handle = spawn(async move {
+    let (mut stream, _) = listener.accept().await.unwrap();
+    let mut bodies = Vec::new();
+
+    for reply in replies {
+      let head = recognize(&mut stream).await.unwrap();
+      let mut body = String::new();
+      Body::new(&head, &mut stream)
+        .unwrap()
+        .read_to_string(&mut body)
+        .await
+        .unwrap();
+      bodies.push(body);
+      stream.write_all(reply.as_bytes()).await.unwrap();
+    }
+
+    bodies
+  });
+
+  (TcpStream::connect(addr).await.unwrap(), handle)
+}
+
+#[test]
+fn client_keep_alive() {
+  block_on(async {
+    let (stream, server) = stand_in(&[
+      "HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nfirst",
+      "HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 201 Created\r\nTransfer-Encoding: chunked\r\n\r\n6\r\nsecond\r\n0\r\n\r\n",
+      "HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\n",
+    ])
+    .await;
+
+    let head = request(&["GET /first HTTP/1.1", "Host: localhost"]);
+    let (response, mut body) = client::send(&stream, &head, b"").await.unwrap();
+    assert_eq!(response.status(), Some(200));
+    let mut contents = String::new();
+    body.read_to_string(&mut contents).await.unwrap();
+    assert_eq!(contents, "first");
+
+    let head = request(&["POST /second HTTP/1.1", "Host: localhost", "Content-Length: 3"]);
+    let (response, mut body) = client::send(&stream, &head, b"hey").await.unwrap();
+    assert_eq!(response.status(), Some(201));
+    let mut contents = String::new();
+    body.read_to_string(&mut contents).await.unwrap();
+    assert_eq!(contents, "second");
+
+    let head = request(&["HEAD /third HTTP/1.1", "Host: localhost"]);
+    let (response, body) = client::send(&stream, &head, b"").await.unwrap();
+    assert_eq!(response.len(), Some(5));
+    assert!(body.is_done());
+
+    assert_eq!(server.await, vec!["", "hey", ""]);
+  });
+}
+
+#[test]
+fn client_close_delimited() {
+  block_on(async {
+    let (stream, server) = stand_in(&["HTTP/1.0 200 OK\r\n\r\nuntil close"]).await;
+    let head = request(&["GET / HTTP/1.0"]);
+    let (response, mut body) = client::send(&stream, &head, b"").await.unwrap();
+    assert_eq!(response.status(), Some(200));
+
+    // The stand-in closes the connection once it has replied.
+    server.await;
+    let mut contents = String::new();
+    body.read_to_string(&mut contents).await.unwrap();
+    assert_eq!(contents, "until close");
+  });
+}
+
+#[test]
+fn client_folded_response() {
+  block_on(async {
+    let (stream, server) =
+      stand_in(&["HTTP/1.1 200 OK\r\nX-Folded: one\r\n  two\r\nContent-Length: 4\r\n\r\nbody"]).await;
+    let head = request(&["GET / HTTP/1.1", "Host: localhost"]);
+    let (response, mut body) = client::send(&stream, &head, b"").await.unwrap();
+    assert_eq!(response.find_header("x-folded"), Some("one two".to_string()));
+    let mut contents = String::new();
+    body.read_to_string(&mut contents).await.unwrap();
+    assert_eq!(contents, "body");
+    server.await;
+  });
+}
+
+#[test]
+fn client_request_framing() {
+  block_on(async {
+    let (stream, server) = stand_in(&["HTTP/1.1 204 No Content\r\n\r\n", "HTTP/1.1 204 No Content\r\n\r\n"]).await;
+
+    // Mismatched bodies fail before anything is written.
+    let head = request(&["POST / HTTP/1.1", "Content-Length: 5"]);
+    let result = client::send(&stream, &head, b"hey").await;
+    assert!(matches!(result, Err(Error::InvalidContentLength(_))));
+    let head = request(&["POST / HTTP/1.1", "Transfer-Encoding: chunked"]);
+    let result = client::send(&stream, &head, b"3\r\nhey\r\n").await;
+    assert!(matches!(result, Err(Error::InvalidChunk)));
+    let result = client::send(&stream, &head, b"3\r\nhey\r\n0\r\n\r\nGET / HTTP/1.1\r\n\r\n").await;
+    assert!(matches!(result, Err(Error::InvalidChunk)));
+
+    let head = request(&["POST /length HTTP/1.1"]);
+    let (response, _) = client::send(&stream, &head, b"hey").await.unwrap();
+    assert_eq!(response.status(), Some(204));
+    let head = request(&["POST /chunked HTTP/1.1", "Transfer-Encoding: chunked"]);
+    let (response, _) = client::send(&stream, &head, b"3\r\nhey\r\n0\r\n\r\n").await.unwrap();
+    assert_eq!(response.status(), Some(204));
+
+    assert_eq!(server.await, vec!["hey", "hey"]);
+  });
+}
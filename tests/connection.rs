@@ -0,0 +1,122 @@
+#![cfg(test)]
+
+mod helpers;
+
+use async_std::prelude::*;
+use async_std::task::block_on;
+use elaine::{Connection, Error};
+use helpers::AsyncBuffer;
+
+#[test]
+fn connection_pipelined() {
+  let mut connection = Connection::new(AsyncBuffer::new(
+    "POST /one HTTP/1.1\r\nContent-Length: 3\r\n\r\nheyGET /two HTTP/1.1\r\n\r\nGET /three HTTP/1.1\r\n\r\n",
+  ));
+
+  let paths = block_on(async {
+    let mut paths = Vec::new();
+    while let Some(result) = connection.next().await {
+      let (head, body) = result.unwrap();
+      let mut contents = String::new();
+      body.read_to_string(&mut contents).await.unwrap();
+      paths.push((head.path().unwrap(), contents));
+    }
+    paths
+  });
+
+  assert_eq!(
+    paths,
+    vec![
+      ("/one".to_string(), "hey".to_string()),
+      ("/two".to_string(), String::new()),
+      ("/three".to_string(), String::new()),
+    ]
+  );
+}
+
+#[test]
+fn connection_skips_unread_body() {
+  let mut connection = Connection::new(AsyncBuffer::new(
+    "POST /one HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nhey\r\n0\r\n\r\nGET /two HTTP/1.1\r\n\r\n",
+  ));
+
+  block_on(async {
+    let (head, _) = connection.next().await.unwrap().unwrap();
+    assert_eq!(head.path(), Some("/one".to_string()));
+    let (head, _) = connection.next().await.unwrap().unwrap();
+    assert_eq!(head.path(), Some("/two".to_string()));
+    assert!(connection.next().await.is_none());
+  });
+}
+
+#[test]
+fn connection_close() {
+  let source: &[u8] = b"GET /one HTTP/1.1\r\nConnection: close\r\n\r\nGET /two HTTP/1.1\r\n\r\n";
+  let mut connection = Connection::new(source);
+
+  block_on(async {
+    let (head, _) = connection.next().await.unwrap().unwrap();
+    assert_eq!(head.path(), Some("/one".to_string()));
+    assert!(!connection.is_persistent());
+    assert!(connection.next().await.is_none());
+  });
+
+  assert_eq!(connection.into_inner(), Some(&b"GET /two HTTP/1.1\r\n\r\n"[..]));
+}
+
+#[test]
+fn connection_close_options() {
+  for options in ["keep-alive, CLOSE", "close, , te", "close;q=1"] {
+    let source = format!(
+      "GET /one HTTP/1.1\r\nConnection: {}\r\n\r\nGET /two HTTP/1.1\r\n\r\n",
+      options
+    );
+    let mut connection = Connection::new(source.as_bytes());
+
+    block_on(async {
+      connection.next().await.unwrap().unwrap();
+      assert!(!connection.is_persistent(), "{}", options);
+    });
+  }
+}
+
+#[test]
+fn connection_http10_defaults() {
+  let source: &[u8] =
+    b"GET /one HTTP/1.0\r\nConnection: keep-alive\r\n\r\nGET /two HTTP/1.0\r\n\r\nGET /three HTTP/1.0\r\n\r\n";
+  let mut connection = Connection::new(source);
+
+  let paths = block_on(async {
+    let mut paths = Vec::new();
+    while let Some(result) = connection.next().await {
+      paths.push(result.unwrap().0.path().unwrap());
+    }
+    paths
+  });
+
+  assert_eq!(paths, vec!["/one", "/two"]);
+}
+
+#[test]
+fn connection_closed_by_server() {
+  let source: &[u8] = b"GET /one HTTP/1.1\r\n\r\nGET /two HTTP/1.1\r\n\r\n";
+  let mut connection = Connection::new(source);
+
+  block_on(async {
+    assert!(connection.next().await.unwrap().is_ok());
+    connection.close();
+    assert!(connection.next().await.is_none());
+  });
+}
+
+#[test]
+fn connection_errors() {
+  let mut connection = Connection::new(AsyncBuffer::new("GET /one HTTP/1.1\r\n\r\nGET /two HTTP/1.1\r\nHost"));
+
+  block_on(async {
+    assert!(connection.next().await.unwrap().is_ok());
+    let result = connection.next().await.unwrap().map(|(head, _)| head);
+    assert!(matches!(result, Err(Error::UnexpectedEof(_))), "{:?}", result);
+    assert!(connection.next().await.is_none());
+  });
+}
@@ -1,6 +1,6 @@
 #![cfg(test)]
 
-use async_std::io::Read;
+use async_std::io::{Read, Write};
 use async_std::task::{Context, Poll};
 use std::collections::VecDeque;
 use std::io::Error;
@@ -48,3 +48,75 @@ impl Read for AsyncBuffer {
     Poll::Ready(Ok(written))
   }
 }
+
+/// Bytes written are appended to the source, so that they can be read back.
+impl Write for AsyncBuffer {
+  fn poll_write(mut self: Pin<&mut Self>, _cx: &mut Context, source: &[u8]) -> Poll<Result<usize, Error>> {
+    self.source.extend(source);
+    Poll::Ready(Ok(source.len()))
+  }
+
+  fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<Result<(), Error>> {
+    Poll::Ready(Ok(()))
+  }
+
+  fn poll_close(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<Result<(), Error>> {
+    Poll::Ready(Ok(()))
+  }
+}
+
+/// Hands out at most one byte per read, the way a slow peer might.
+pub struct Trickle {
+  inner: AsyncBuffer,
+}
+
+impl Trickle {
+  #[allow(dead_code)]
+  pub fn new<S>(inner: S) -> Self
+  where
+    S: Into<String>,
+  {
+    Trickle {
+      inner: AsyncBuffer::new(inner),
+    }
+  }
+}
+
+impl Read for Trickle {
+  fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context, dest: &mut [u8]) -> Poll<Result<usize, Error>> {
+    let end = std::cmp::min(dest.len(), 1);
+    Pin::new(&mut self.inner).poll_read(cx, &mut dest[0..end])
+  }
+}
+
+impl std::io::Read for AsyncBuffer {
+  fn read(&mut self, dest: &mut [u8]) -> Result<usize, Error> {
+    let mut written = 0;
+
+    for b in &mut *dest {
+      match self.source.pop_front() {
+        Some(byte) => {
+          *b = byte;
+          written += 1;
+        }
+        None => break,
+      }
+    }
+
+    Ok(written)
+  }
+}
+
+#[cfg(feature = "tokio")]
+impl tokio::io::AsyncRead for AsyncBuffer {
+  fn poll_read(mut self: Pin<&mut Self>, _cx: &mut Context, dest: &mut tokio::io::ReadBuf) -> Poll<Result<(), Error>> {
+    while dest.remaining() > 0 {
+      match self.source.pop_front() {
+        Some(byte) => dest.put_slice(&[byte]),
+        None => break,
+      }
+    }
+
+    Poll::Ready(Ok(()))
+  }
+}
@@ -0,0 +1,144 @@
+#![cfg(test)]
+
+use elaine::{blocking, Error, LimitExceeded, Limits, Parser, RecognizeOptions, ResponseParser, Status};
+
+const REQUEST: &[u8] = b"POST /parse HTTP/1.1\r\nHost: example.com\r\nContent-Length: 3\r\n\r\nhey";
+
+#[test]
+fn parser_single_feed() {
+  let mut parser = Parser::new();
+  match parser.feed(REQUEST) {
+    Status::Complete(head, consumed) => {
+      assert_eq!(head.path(), Some("/parse".to_string()));
+      assert_eq!(head.len(), Some(3));
+      assert_eq!(&REQUEST[consumed..], b"hey");
+    }
+    other => panic!("unexpected {:?}", other),
+  }
+}
+
+#[test]
+fn parser_byte_at_a_time() {
+  let mut parser = Parser::new();
+  let mut result = None;
+
+  for (index, byte) in REQUEST.iter().enumerate() {
+    match parser.feed(&[*byte]) {
+      Status::NeedMore => continue,
+      Status::Complete(head, consumed) => {
+        assert_eq!(consumed, 1);
+        result = Some((head, index + 1));
+        break;
+      }
+      Status::Error(e) => panic!("unexpected {:?}", e),
+    }
+  }
+
+  let (head, end) = result.unwrap();
+  assert_eq!(head.to_string(), blocking::recognize(REQUEST).unwrap().to_string());
+  assert_eq!(&REQUEST[end..], b"hey");
+}
+
+#[test]
+fn parser_wanted() {
+  let source = "GET /ünïcödé HTTP/1.1\r\nComplex: aaࡘ\r\n\r\nnext".as_bytes();
+  let mut rest = source;
+  let mut parser = Parser::new();
+
+  let head = loop {
+    let wanted = std::cmp::min(parser.wanted(), rest.len());
+    let (input, after) = rest.split_at(wanted);
+    rest = after;
+
+    match parser.feed(input) {
+      Status::NeedMore => continue,
+      Status::Complete(head, consumed) => {
+        assert_eq!(consumed, input.len());
+        break head;
+      }
+      Status::Error(e) => panic!("unexpected {:?}", e),
+    }
+  };
+
+  assert_eq!(head.find_header("complex"), Some("aaࡘ".to_string()));
+  assert_eq!(rest, b"next");
+  assert_eq!(parser.wanted(), 0);
+}
+
+#[test]
+fn parser_errors() {
+  let mut parser = Parser::new();
+  assert!(matches!(
+    parser.feed(b"a\r\n\r\n"),
+    Status::Error(Error::InvalidRequestLine(_))
+  ));
+  assert!(matches!(parser.feed(b"GET / HTTP/1.1\r\n\r\n"), Status::Error(_)));
+
+  let options = RecognizeOptions {
+    limits: Limits {
+      head: 16,
+      ..Limits::default()
+    },
+    ..RecognizeOptions::default()
+  };
+  let mut parser = Parser::with_options(&options);
+  let result = parser.feed(REQUEST);
+  assert!(
+    matches!(result, Status::Error(Error::LimitExceeded(LimitExceeded::HeadSize, _))),
+    "{:?}",
+    result
+  );
+}
+
+#[test]
+fn parser_eof() {
+  let mut parser = Parser::new();
+  assert!(matches!(parser.feed(b"GET / HTTP/1.1\r\nHost"), Status::NeedMore));
+  assert!(matches!(parser.eof(), Status::Error(Error::UnexpectedEof(_))));
+
+  let options = RecognizeOptions {
+    http09: true,
+    ..RecognizeOptions::default()
+  };
+  let mut parser = Parser::with_options(&options);
+  assert!(matches!(parser.feed(b"GET /legacy\r\nnext"), Status::Complete(_, 13)));
+  let mut parser = Parser::with_options(&options);
+  assert!(matches!(
+    parser.feed(b"\r\nGET /legacy\r\nnext"),
+    Status::Complete(_, 15)
+  ));
+}
+
+// Empty lines are skipped until a start line arrives; they never make up a head of their own.
+#[test]
+fn parser_empty_lines() {
+  let mut parser = Parser::new();
+  assert!(matches!(parser.feed(b"\r\n\r\n"), Status::NeedMore));
+  assert!(matches!(parser.eof(), Status::Error(Error::UnexpectedEof(_))));
+
+  let mut parser = Parser::new();
+  assert!(matches!(parser.feed(b"\r\n\r\n\r\n"), Status::NeedMore));
+  match parser.feed(b"GET / HTTP/1.1\r\n\r\nrest") {
+    Status::Complete(head, consumed) => {
+      assert_eq!(head.path(), Some("/".to_string()));
+      assert_eq!(consumed, 18);
+    }
+    other => panic!("unexpected {:?}", other),
+  }
+
+  let result = blocking::recognize(&b"\r\n\r\n"[..]);
+  assert!(matches!(result, Err(Error::UnexpectedEof(_))), "{:?}", result);
+}
+
+#[test]
+fn parser_response() {
+  let mut parser = ResponseParser::new();
+  assert!(matches!(parser.feed(b"HTTP/1.1 404 Not Found\r\n"), Status::NeedMore));
+  match parser.feed(b"Content-Length: 0\r\n\r\n") {
+    Status::Complete(head, consumed) => {
+      assert_eq!(head.status(), Some(404));
+      assert_eq!(consumed, 21);
+    }
+    other => panic!("unexpected {:?}", other),
+  }
+}
@@ -0,0 +1,108 @@
+#![cfg(test)]
+
+use async_std::io::BufReader;
+use async_std::task::block_on;
+use elaine::{
+  blocking, recognize, recognize_buffered_with, recognize_with, Error, RecognizeOptions, RequestMethod, RequestVersion,
+  HTTP2_PREFACE,
+};
+use std::io::Read;
+
+const FRAME: &[u8] = b"\x00\x00\x00\x04\x00\x00\x00\x00\x00";
+
+fn preface() -> Vec<u8> {
+  [HTTP2_PREFACE, FRAME].concat()
+}
+
+fn remaining<R: Read>(mut reader: R) -> Vec<u8> {
+  let mut rest = Vec::new();
+  reader.read_to_end(&mut rest).unwrap();
+  rest
+}
+
+fn simple() -> RecognizeOptions {
+  RecognizeOptions {
+    http09: true,
+    ..RecognizeOptions::default()
+  }
+}
+
+#[test]
+fn preface_recognize() {
+  for options in [RecognizeOptions::default(), simple()].iter() {
+    let source = preface();
+    let mut reader = &source[..];
+    let result = block_on(async { recognize_with(&mut reader, options).await });
+    assert!(matches!(result, Err(Error::Http2Preface)), "{:?}", result);
+    assert_eq!(reader, FRAME);
+  }
+}
+
+#[test]
+fn preface_buffered() {
+  for options in [RecognizeOptions::default(), simple()].iter() {
+    let source = preface();
+    let mut reader = BufReader::with_capacity(7, &source[..]);
+    let result = block_on(async { recognize_buffered_with(&mut reader, options).await });
+    assert!(matches!(result, Err(Error::Http2Preface)), "{:?}", result);
+
+    let mut reader = std::io::BufReader::new(&source[..]);
+    let result = blocking::recognize_buffered_with(&mut reader, options);
+    assert!(matches!(result, Err(Error::Http2Preface)), "{:?}", result);
+    assert_eq!(remaining(reader), FRAME);
+  }
+}
+
+#[test]
+fn preface_blocking() {
+  let source = preface();
+  let mut reader = &source[..];
+  assert!(matches!(blocking::recognize(&mut reader), Err(Error::Http2Preface)));
+  assert_eq!(reader, FRAME);
+}
+
+#[test]
+fn preface_mismatch() {
+  let mut reader: &[u8] = b"PRI * HTTP/2.0\r\n\r\nXX\r\n\r\n";
+  let result = block_on(async { recognize(&mut reader).await });
+  assert!(matches!(result, Err(Error::InvalidRequestLine(_))), "{:?}", result);
+}
+
+#[test]
+fn simple_request() {
+  let mut reader: &[u8] = b"GET /legacy\r\nnext";
+  let head = block_on(async { recognize_with(&mut reader, &simple()).await }).unwrap();
+  assert_eq!(head.method(), Some(RequestMethod::GET));
+  assert_eq!(head.path(), Some("/legacy".to_string()));
+  assert_eq!(head.version(), Some(RequestVersion::Simple));
+  assert_eq!(head.to_string(), "GET /legacy\r\n");
+  assert_eq!(reader, b"next");
+
+  let mut reader: &[u8] = b"GET /legacy\r\nnext";
+  let head = blocking::recognize_with(&mut reader, &simple()).unwrap();
+  assert_eq!(head.version(), Some(RequestVersion::Simple));
+  assert_eq!(reader, b"next");
+
+  let mut reader = std::io::BufReader::with_capacity(5, &b"GET /legacy\r\nnext"[..]);
+  let head = blocking::recognize_buffered_with(&mut reader, &simple()).unwrap();
+  assert_eq!(head.version(), Some(RequestVersion::Simple));
+  assert_eq!(remaining(reader), b"next");
+}
+
+#[test]
+fn simple_request_options() {
+  let mut reader: &[u8] = b"GET /legacy\r\n\r\n";
+  let result = block_on(async { recognize(&mut reader).await });
+  assert!(matches!(result, Err(Error::InvalidRequestLine(_))), "{:?}", result);
+
+  // Full requests are unaffected by accepting simple ones.
+  let mut reader: &[u8] = b"GET /modern HTTP/1.1\r\nHost: example.com\r\n\r\nnext";
+  let head = block_on(async { recognize_with(&mut reader, &simple()).await }).unwrap();
+  assert_eq!(head.version(), Some(RequestVersion::RFC2616));
+  assert_eq!(head.find_header("host"), Some("example.com".to_string()));
+  assert_eq!(reader, b"next");
+
+  let mut reader: &[u8] = b"POST /legacy\r\n\r\n";
+  let result = block_on(async { recognize_with(&mut reader, &simple()).await });
+  assert!(matches!(result, Err(Error::InvalidRequestLine(_))), "{:?}", result);
+}
@@ -4,9 +4,11 @@ mod helpers;
 
 use async_std::prelude::*;
 use async_std::task::block_on;
-use elaine::{recognize, RequestMethod};
+use elaine::{
+  recognize, recognize_with, BodyFraming, Builder, Credentials, Diagnostic, Error, Head, HeaderParsing, Host,
+  LimitExceeded, Limits, ObsFold, Position, RecognizeOptions, RequestMethod, RequestTarget,
+};
 use helpers::AsyncBuffer;
-use std::io::{Error, ErrorKind};
 
 fn buffer_from(source: &[u8]) -> AsyncBuffer {
   AsyncBuffer::new(format!(
@@ -24,11 +26,21 @@ fn test_invalid_header_line() {
 
 #[test]
 fn test_invalid_method() {
-  let mut buff = AsyncBuffer::new("FOOBAR /hello HTTP/1.1\r\nHost: 0.0.0.0:8080\r\n\r\n");
+  let mut buff = AsyncBuffer::new("FOO[BAR] /hello HTTP/1.1\r\nHost: 0.0.0.0:8080\r\n\r\n");
   let result = block_on(async { recognize(&mut buff).await });
   assert!(result.is_err());
 }
 
+#[test]
+fn test_extension_method() {
+  let mut buff = AsyncBuffer::new("PROPFIND /hello HTTP/1.1\r\nDepth: 1\r\n\r\n");
+  let result = block_on(async { recognize(&mut buff).await });
+  assert_eq!(
+    result.unwrap().method(),
+    Some(RequestMethod::Extension("PROPFIND".to_string()))
+  );
+}
+
 #[test]
 fn test_invalid_version() {
   let mut buff = AsyncBuffer::new("FOOBAR /hello GARBAGE\r\nHost: 0.0.0.0:8080\r\n\r\n");
@@ -207,43 +219,503 @@ fn recognize_and_read_after() {
 fn empty_buffer_error() {
   let mut buffer = AsyncBuffer::new("");
   let result = block_on(async { recognize(&mut buffer).await });
-  assert!(result.is_err());
-  assert_eq!(
-    format!("{:?}", result.unwrap_err()),
-    format!(
-      "{:?}",
-      Error::new(
-        ErrorKind::UnexpectedEof,
-        "Reader exhausted before terminating HTTP head"
-      )
-    )
-  );
+  match result {
+    Err(Error::UnexpectedEof(position)) => assert_eq!(position, Position { offset: 0, line: 1 }),
+    other => panic!("unexpected result: {:?}", other),
+  }
 }
 
 #[test]
 fn invalid_request_line_error() {
   let mut buffer = AsyncBuffer::new("a\r\na");
   let result = block_on(async { recognize(&mut buffer).await });
-  assert!(result.is_err());
-  assert_eq!(
-    format!("{:?}", result.unwrap_err()),
-    format!("{:?}", Error::new(ErrorKind::InvalidData, "Invalid request line: 'a'"))
-  );
+  match result {
+    Err(Error::InvalidRequestLine(position)) => assert_eq!(position, Position { offset: 0, line: 1 }),
+    other => panic!("unexpected result: {:?}", other),
+  }
 }
 
 #[test]
 fn invalid_termination() {
   let mut buffer = AsyncBuffer::new("GET / HTTP/1.0\r\nbad-line");
   let result = block_on(async { recognize(&mut buffer).await });
-  assert!(result.is_err());
+  match result {
+    Err(Error::UnexpectedEof(position)) => assert_eq!(position, Position { offset: 24, line: 2 }),
+    other => panic!("unexpected result: {:?}", other),
+  }
+}
+
+#[test]
+fn invalid_version_position() {
+  let mut buffer = AsyncBuffer::new("GET / HTTP/1.1\r\nHost: example.com\r\n\r\n");
+  assert!(block_on(async { recognize(&mut buffer).await }).is_ok());
+  let mut buffer = AsyncBuffer::new("GET / HTTP/9\r\nHost: example.com\r\n\r\n");
+  let result = block_on(async { recognize(&mut buffer).await });
+  match result {
+    Err(Error::InvalidVersion(position)) => assert_eq!(position, Position { offset: 0, line: 1 }),
+    other => panic!("unexpected result: {:?}", other),
+  }
+}
+
+#[test]
+fn invalid_content_length_position() {
+  let mut buffer = AsyncBuffer::new("GET / HTTP/1.1\r\nHost: example.com\r\nContent-Length: ten\r\n\r\n");
+  let result = block_on(async { recognize(&mut buffer).await });
+  match result {
+    Err(Error::InvalidContentLength(position)) => assert_eq!(position, Position { offset: 35, line: 3 }),
+    other => panic!("unexpected result: {:?}", other),
+  }
+}
+
+#[test]
+fn invalid_utf8_error() {
+  let mut buffer: &[u8] = b"GET / HTTP/1.1\r\nHost: \xff\r\n\r\n";
+  let result = block_on(async { recognize(&mut buffer).await });
+  assert!(matches!(result, Err(Error::InvalidUtf8(_))));
+}
+
+#[test]
+fn error_into_io_error() {
+  let mut buffer = AsyncBuffer::new("GET / HTTP/1.1\r\n");
+  let result: Result<Head, std::io::Error> = block_on(async { Ok(recognize(&mut buffer).await?) });
+  assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::UnexpectedEof);
+}
+
+fn limit_of(error: &Error) -> Option<LimitExceeded> {
+  match error {
+    Error::LimitExceeded(limit, _) => Some(*limit),
+    _ => None,
+  }
+}
+
+#[test]
+fn recognize_request_line_limit() {
+  let options = RecognizeOptions {
+    limits: Limits {
+      request_line: 16,
+      ..Limits::default()
+    },
+    ..RecognizeOptions::default()
+  };
+  let mut buffer = AsyncBuffer::new("GET /a-long-path-name HTTP/1.1\r\n\r\n");
+  let result = block_on(async { recognize_with(&mut buffer, &options).await });
+  assert_eq!(limit_of(&result.unwrap_err()), Some(LimitExceeded::RequestLine));
+}
+
+#[test]
+fn recognize_header_line_limit() {
+  let options = RecognizeOptions {
+    limits: Limits {
+      header_line: 16,
+      ..Limits::default()
+    },
+    ..RecognizeOptions::default()
+  };
+  let mut buffer = AsyncBuffer::new("GET /a-long-path-name HTTP/1.1\r\nX-Long: 0123456789abcdef\r\n\r\n");
+  let result = block_on(async { recognize_with(&mut buffer, &options).await });
+  assert_eq!(limit_of(&result.unwrap_err()), Some(LimitExceeded::HeaderLine));
+}
+
+#[test]
+fn recognize_header_count_limit() {
+  let options = RecognizeOptions {
+    limits: Limits {
+      header_count: 2,
+      ..Limits::default()
+    },
+    ..RecognizeOptions::default()
+  };
+  let mut buffer = AsyncBuffer::new("GET / HTTP/1.1\r\nA: 1\r\nB: 2\r\n\r\n");
+  assert!(block_on(async { recognize_with(&mut buffer, &options).await }).is_ok());
+  let mut buffer = AsyncBuffer::new("GET / HTTP/1.1\r\nA: 1\r\nB: 2\r\nC: 3\r\n\r\n");
+  let result = block_on(async { recognize_with(&mut buffer, &options).await });
+  assert_eq!(limit_of(&result.unwrap_err()), Some(LimitExceeded::HeaderCount));
+}
+
+#[test]
+fn recognize_head_size_limit() {
+  let options = RecognizeOptions {
+    limits: Limits {
+      head: 32,
+      ..Limits::default()
+    },
+    ..RecognizeOptions::default()
+  };
+  let mut buffer = AsyncBuffer::new("GET / HTTP/1.1\r\nA: 1\r\nB: 2\r\nC: 3\r\nD: 4\r\n\r\n");
+  let result = block_on(async { recognize_with(&mut buffer, &options).await });
+  assert_eq!(limit_of(&result.unwrap_err()), Some(LimitExceeded::HeadSize));
+}
+
+#[test]
+fn recognize_default_limits() {
+  let mut buffer = AsyncBuffer::new(format!("GET / HTTP/1.1\r\nX-Long: {}\r\n\r\n", "a".repeat(10 * 1024)));
+  let result = block_on(async { recognize(&mut buffer).await });
+  assert_eq!(limit_of(&result.unwrap_err()), Some(LimitExceeded::HeaderLine));
+}
+
+#[test]
+fn recognize_short_reads() {
+  let mut buffer = helpers::Trickle::new("GET /short HTTP/1.1\r\nHost: example.com\r\n\r\nnext");
+  let head = block_on(async { recognize(&mut buffer).await }).unwrap();
+  assert_eq!(head.path(), Some("/short".to_string()));
+  assert_eq!(head.find_header("host"), Some("example.com".to_string()));
+}
+
+#[test]
+fn recognize_repeated_headers() {
+  let mut buffer = AsyncBuffer::new(
+    "GET / HTTP/1.1\r\nAccept: text/html\r\nForwarded: for=192.0.2.60\r\naccept: application/json\r\n\r\n",
+  );
+  let head = block_on(async { recognize(&mut buffer).await }).unwrap();
+  let headers = head.headers();
+  assert_eq!(headers.len(), 3);
+  assert_eq!(head.find_header("ACCEPT"), Some("text/html".to_string()));
+  assert_eq!(
+    headers.combined("Accept"),
+    Some("text/html, application/json".to_string())
+  );
+  let fields = headers.iter().collect::<Vec<(&str, &str)>>();
   assert_eq!(
-    format!("{:?}", result.unwrap_err()),
-    format!(
-      "{:?}",
-      Error::new(
-        ErrorKind::UnexpectedEof,
-        "Reader exhausted before terminating HTTP head (last read 'bad-line')"
-      )
-    )
+    fields,
+    vec![
+      ("Accept", "text/html"),
+      ("Forwarded", "for=192.0.2.60"),
+      ("accept", "application/json")
+    ]
   );
 }
+
+#[test]
+fn recognize_and_write_to() {
+  let raw = "GET /proxy?a=b HTTP/1.1\r\nHost: example.com\r\nAccept: */*\r\naccept: text/html\r\n\r\n";
+  let mut buffer = AsyncBuffer::new(raw);
+  let mut output = Vec::new();
+  block_on(async {
+    let head = recognize(&mut buffer).await.unwrap();
+    head.write_to(&mut output).await.unwrap();
+  });
+  assert_eq!(String::from_utf8(output).unwrap(), raw);
+}
+
+#[test]
+fn recognize_request_targets() {
+  let cases = [
+    (
+      "GET /search?q=elaine HTTP/1.1",
+      RequestTarget::parse("/search?q=elaine").unwrap(),
+    ),
+    ("OPTIONS * HTTP/1.1", RequestTarget::Asterisk),
+    (
+      "CONNECT example.com:443 HTTP/1.1",
+      RequestTarget::Authority("example.com:443".to_string()),
+    ),
+    (
+      "GET http://example.com/a HTTP/1.1",
+      RequestTarget::parse("http://example.com/a").unwrap(),
+    ),
+  ];
+
+  for (line, expected) in cases.iter() {
+    let mut buffer = AsyncBuffer::new(format!("{}\r\n\r\n", line));
+    let head = block_on(async { recognize(&mut buffer).await }).unwrap();
+    assert_eq!(head.target().as_ref(), Some(expected), "{}", line);
+  }
+}
+
+#[test]
+fn invalid_request_target() {
+  for line in [
+    "GET * HTTP/1.1",
+    "CONNECT /tunnel HTTP/1.1",
+    "GET example.com:443 HTTP/1.1",
+    "GET /a#b HTTP/1.1",
+  ]
+  .iter()
+  {
+    let mut buffer = AsyncBuffer::new(format!("{}\r\n\r\n", line));
+    match block_on(async { recognize(&mut buffer).await }) {
+      Err(Error::InvalidRequestTarget(position)) => assert_eq!(position, Position { offset: 0, line: 1 }),
+      other => panic!("unexpected result for {}: {:?}", line, other),
+    }
+  }
+}
+
+#[test]
+fn recognize_header_whitespace() {
+  let mut buffer = AsyncBuffer::new("GET / HTTP/1.1\r\nHost:example.com\r\nAccept:   text/html  \r\n\r\n");
+  let head = block_on(async { recognize(&mut buffer).await }).unwrap();
+  assert_eq!(head.find_header("host"), Some("example.com".to_string()));
+  assert_eq!(head.find_header("accept"), Some("text/html".to_string()));
+}
+
+#[test]
+fn whitespace_before_colon() {
+  let mut buffer = AsyncBuffer::new("POST / HTTP/1.1\r\nHost: example.com\r\nContent-Length : 3\r\n\r\nabc");
+  match block_on(async { recognize(&mut buffer).await }) {
+    Err(Error::InvalidHeader(position)) => assert_eq!(position, Position { offset: 36, line: 3 }),
+    other => panic!("unexpected result: {:?}", other),
+  }
+}
+
+#[test]
+fn obs_fold_rejected() {
+  let mut buffer = AsyncBuffer::new("GET / HTTP/1.1\r\nX-Folded: one\r\n  two\r\nHost: example.com\r\n\r\n");
+  match block_on(async { recognize(&mut buffer).await }) {
+    Err(Error::InvalidHeader(position)) => assert_eq!(position, Position { offset: 31, line: 3 }),
+    other => panic!("unexpected result: {:?}", other),
+  }
+}
+
+#[test]
+fn obs_fold_unfolded() {
+  let options = RecognizeOptions {
+    obs_fold: ObsFold::Unfold,
+    ..RecognizeOptions::default()
+  };
+  let mut buffer =
+    AsyncBuffer::new("GET / HTTP/1.1\r\nX-Folded: one\r\n  two\r\n\tthree \r\nHost: example.com\r\n\r\n");
+  let head = block_on(async { recognize_with(&mut buffer, &options).await }).unwrap();
+  assert_eq!(head.find_header("x-folded"), Some("one two three".to_string()));
+  assert_eq!(head.headers().len(), 2);
+
+  let mut buffer = AsyncBuffer::new("GET / HTTP/1.1\r\n folded\r\n\r\n");
+  let result = block_on(async { recognize_with(&mut buffer, &options).await });
+  assert!(matches!(result, Err(Error::InvalidHeader(_))), "{:?}", result);
+}
+
+#[test]
+fn strict_header_parsing() {
+  let options = RecognizeOptions {
+    header_parsing: HeaderParsing::Strict,
+    ..RecognizeOptions::default()
+  };
+  let lines = ["FOOBAR", ": no-name", "Bad{Name}: value", "Ünïcode: value"];
+
+  for line in lines.iter() {
+    let mut buffer = AsyncBuffer::new(format!("GET / HTTP/1.1\r\nHost: example.com\r\n{}\r\n\r\n", line));
+    match block_on(async { recognize_with(&mut buffer, &options).await }) {
+      Err(Error::InvalidHeader(position)) => assert_eq!(position, Position { offset: 35, line: 3 }, "{}", line),
+      other => panic!("unexpected result for {}: {:?}", line, other),
+    }
+  }
+}
+
+#[test]
+fn lenient_header_diagnostics() {
+  let mut buffer = AsyncBuffer::new("GET / HTTP/1.1\r\nFOOBAR\r\nHost: example.com\r\nBad{Name}: value\r\n\r\n");
+  let head = block_on(async { recognize(&mut buffer).await }).unwrap();
+  assert_eq!(head.find_header("host"), Some("example.com".to_string()));
+  assert_eq!(head.headers().len(), 1);
+  assert_eq!(
+    head.diagnostics(),
+    &[
+      Diagnostic {
+        position: Position { offset: 16, line: 2 },
+        line: "FOOBAR".to_string(),
+      },
+      Diagnostic {
+        position: Position { offset: 43, line: 4 },
+        line: "Bad{Name}: value".to_string(),
+      },
+    ][..]
+  );
+}
+
+#[test]
+fn control_characters_rejected() {
+  let strict = RecognizeOptions {
+    header_parsing: HeaderParsing::Strict,
+    ..RecognizeOptions::default()
+  };
+  let unfold = RecognizeOptions {
+    obs_fold: ObsFold::Unfold,
+    ..RecognizeOptions::default()
+  };
+  let lines = [
+    "X: a\nTransfer-Encoding: chunked",
+    "X: a\rb",
+    "X: a\0b",
+    "X: a\x7fb",
+    "X\x01: a",
+    "FOO\nBAR",
+  ];
+
+  for options in [RecognizeOptions::default(), strict, unfold.clone()].iter() {
+    for line in lines.iter() {
+      let mut buffer = AsyncBuffer::new(format!("POST / HTTP/1.1\r\n{}\r\nContent-Length: 5\r\n\r\n", line));
+      match block_on(async { recognize_with(&mut buffer, options).await }) {
+        Err(Error::InvalidHeader(position)) => assert_eq!(position, Position { offset: 17, line: 2 }, "{:?}", line),
+        other => panic!("unexpected result for {:?}: {:?}", line, other),
+      }
+    }
+  }
+
+  let mut buffer = AsyncBuffer::new("GET / HTTP/1.1\r\nX: one\r\n two\x0bthree\r\n\r\n");
+  let result = block_on(async { recognize_with(&mut buffer, &unfold).await });
+  assert!(matches!(result, Err(Error::InvalidHeader(_))), "{:?}", result);
+
+  let mut buffer = AsyncBuffer::new("GET / HTTP/1.1\r\nX: a\tb\r\n\r\n");
+  let head = block_on(async { recognize(&mut buffer).await }).unwrap();
+  assert_eq!(head.find_header("x"), Some("a\tb".to_string()));
+}
+
+#[test]
+fn recognize_framing() {
+  let cases = [
+    ("GET / HTTP/1.1\r\n\r\n", BodyFraming::None),
+    ("POST / HTTP/1.1\r\ncontent-length: 12\r\n\r\n", BodyFraming::Length(12)),
+    (
+      "POST / HTTP/1.1\r\nContent-Length: 3\r\nContent-Length: 3, 3\r\n\r\n",
+      BodyFraming::Length(3),
+    ),
+    (
+      "POST / HTTP/1.1\r\ntransfer-encoding: CHUNKED\r\n\r\n",
+      BodyFraming::Chunked,
+    ),
+    (
+      "POST / HTTP/1.1\r\nTransfer-Encoding: gzip\r\nTransfer-Encoding: chunked\r\n\r\n",
+      BodyFraming::Chunked,
+    ),
+  ];
+
+  for (raw, expected) in cases.iter() {
+    let mut buffer = AsyncBuffer::new(*raw);
+    let head = block_on(async { recognize(&mut buffer).await }).unwrap();
+    assert_eq!(head.framing(), *expected, "{:?}", raw);
+  }
+}
+
+#[test]
+fn recognize_smuggling() {
+  let cases = [
+    ("POST / HTTP/1.1\r\nContent-Length: 3\r\ncontent-length: 4\r\n\r\n", 3),
+    ("POST / HTTP/1.1\r\nContent-Length: 3, 4\r\n\r\n", 2),
+    (
+      "POST / HTTP/1.1\r\nContent-Length: 3\r\nTransfer-Encoding: chunked\r\n\r\n",
+      3,
+    ),
+    (
+      "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\nContent-Length: 3\r\n\r\n",
+      3,
+    ),
+    ("POST / HTTP/1.1\r\nTransfer-Encoding: chunked, gzip\r\n\r\n", 3),
+    (
+      "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\nTransfer-Encoding: chunked\r\n\r\n",
+      3,
+    ),
+  ];
+
+  for (raw, line) in cases.iter() {
+    let mut buffer = AsyncBuffer::new(*raw);
+    match block_on(async { recognize(&mut buffer).await }) {
+      Err(Error::InvalidContentLength(position)) | Err(Error::InvalidFraming(position)) => {
+        assert_eq!(position.line, *line, "{:?}", raw)
+      }
+      other => panic!("unexpected result for {:?}: {:?}", raw, other),
+    }
+  }
+}
+
+#[test]
+fn recognize_credentials() {
+  let mut buffer = AsyncBuffer::new(
+    "GET / HTTP/1.1\r\nauthorization: Bearer abc.DEF\r\nProxy-Authorization: Basic dXNlcjpwYXNz\r\n\r\n",
+  );
+  let head = block_on(async { recognize(&mut buffer).await }).unwrap();
+  assert_eq!(head.credentials(), Some(Credentials::Bearer("abc.DEF".to_string())));
+  assert_eq!(
+    head.proxy_credentials(),
+    Some(Credentials::Basic {
+      user: "user".to_string(),
+      password: "pass".to_string()
+    })
+  );
+
+  let head = Builder::from(head)
+    .set_header("Authorization", "Digest realm=\"elaine\"")
+    .unwrap()
+    .collect::<Head>();
+  assert_eq!(
+    head
+      .credentials()
+      .and_then(|credentials| credentials.param("realm").map(String::from)),
+    Some("elaine".to_string())
+  );
+
+  let head = Builder::from(head).remove_header("authorization").collect::<Head>();
+  assert_eq!(head.credentials(), None);
+
+  let mut buffer = AsyncBuffer::new("GET / HTTP/1.1\r\nAuthorization: Basic !!\r\n\r\n");
+  let head = block_on(async { recognize(&mut buffer).await }).unwrap();
+  assert_eq!(head.credentials(), None);
+  assert_eq!(head.find_header("authorization"), Some("Basic !!".to_string()));
+}
+
+#[test]
+fn recognize_typed_headers() {
+  let mut buffer = AsyncBuffer::new(
+    "POST /upload HTTP/1.1\r\nHost: [::1]:8080\r\nContent-Type: multipart/form-data; boundary=----x\r\n\
+     Connection: keep-alive, Upgrade\r\nConnection: TE\r\nUser-Agent: curl/8.0\r\n\
+     Referer: http://example.com/\r\nOrigin: http://example.com\r\n\r\n",
+  );
+  let head = block_on(async { recognize(&mut buffer).await }).unwrap();
+  assert_eq!(
+    head.host().unwrap(),
+    Some(Host {
+      host: "[::1]".to_string(),
+      port: Some(8080)
+    })
+  );
+  let media = head.content_type().unwrap().unwrap();
+  assert_eq!(media.essence(), "multipart/form-data");
+  assert_eq!(media.boundary(), Some("----x"));
+  assert_eq!(head.connection().unwrap(), vec!["keep-alive", "upgrade", "te"]);
+  assert_eq!(head.user_agent(), Some("curl/8.0"));
+  assert_eq!(head.referer(), Some("http://example.com/"));
+  assert_eq!(head.origin(), Some("http://example.com"));
+
+  let mut buffer = AsyncBuffer::new("GET / HTTP/1.1\r\n\r\n");
+  let head = block_on(async { recognize(&mut buffer).await }).unwrap();
+  assert_eq!(head.host().unwrap(), None);
+  assert_eq!(head.content_type().unwrap(), None);
+  assert_eq!(head.connection().unwrap(), Vec::<String>::new());
+  assert_eq!(head.user_agent(), None);
+
+  let mut buffer =
+    AsyncBuffer::new("GET / HTTP/1.1\r\nHost: a.com\r\nHost: b.com\r\nContent-Type: text\r\nConnection: a b\r\n\r\n");
+  let head = block_on(async { recognize(&mut buffer).await }).unwrap();
+  assert!(matches!(head.host(), Err(Error::InvalidHeaderValue("Host"))));
+  assert!(matches!(
+    head.content_type(),
+    Err(Error::InvalidHeaderValue("Content-Type"))
+  ));
+  assert!(matches!(
+    head.connection(),
+    Err(Error::InvalidHeaderValue("Connection"))
+  ));
+  assert_eq!(
+    head.host().unwrap_err().to_string(),
+    "Invalid Host header value".to_string()
+  );
+}
+
+#[test]
+fn recognize_cookies() {
+  let mut buffer = AsyncBuffer::new(
+    "GET / HTTP/1.1\r\nCookie: sid=31d4d96e; lang=\"en-US\";broken; =x\r\nHost: example.com\r\n\
+     cookie: theme=dark\r\n\r\n",
+  );
+  let head = block_on(async { recognize(&mut buffer).await }).unwrap();
+  assert_eq!(
+    head.cookies().collect::<Vec<_>>(),
+    vec![("sid", "31d4d96e"), ("lang", "en-US"), ("theme", "dark")]
+  );
+  assert_eq!(
+    head.cookies().find(|(name, _)| *name == "theme"),
+    Some(("theme", "dark"))
+  );
+
+  let mut buffer = AsyncBuffer::new("GET / HTTP/1.1\r\n\r\n");
+  let head = block_on(async { recognize(&mut buffer).await }).unwrap();
+  assert_eq!(head.cookies().count(), 0);
+}
@@ -0,0 +1,103 @@
+#![cfg(test)]
+
+mod helpers;
+
+use async_std::prelude::*;
+use async_std::task::block_on;
+use elaine::{recognize_response, recognize_response_with, BodyFraming, Error, RecognizeOptions, RequestVersion};
+use helpers::AsyncBuffer;
+
+#[test]
+fn recognize_response_ok() {
+  let mut buffer = AsyncBuffer::new("HTTP/1.1 200 OK\r\nContent-Length: 3\r\n\r\n");
+  let head = block_on(async { recognize_response(&mut buffer).await }).unwrap();
+  assert_eq!(head.version(), Some(RequestVersion::RFC2616));
+  assert_eq!(head.status(), Some(200));
+  assert_eq!(head.reason(), Some("OK".to_string()));
+  assert_eq!(head.len(), Some(3));
+}
+
+#[test]
+fn recognize_response_multi_word_reason() {
+  let mut buffer = AsyncBuffer::new("HTTP/1.0 503 Service Unavailable\r\nRetry-After: 120\r\n\r\n");
+  let head = block_on(async { recognize_response(&mut buffer).await }).unwrap();
+  assert_eq!(head.status(), Some(503));
+  assert_eq!(head.reason(), Some("Service Unavailable".to_string()));
+  assert_eq!(head.find_header("retry-after"), Some("120".to_string()));
+}
+
+#[test]
+fn recognize_response_and_read_after() {
+  let mut buffer = AsyncBuffer::new("HTTP/1.1 200 OK\r\nContent-Length: 3\r\n\r\nhey");
+  let result = block_on(async { recognize_response(&mut buffer).await });
+  assert!(result.is_ok());
+  let mut rem: Vec<u8> = vec![0x00, 0x00, 0x00];
+  let result = block_on(async { buffer.read(&mut rem).await });
+  assert_eq!(result.unwrap(), 3);
+  assert_eq!(String::from_utf8(rem).unwrap(), "hey");
+}
+
+#[test]
+fn recognize_response_request_line() {
+  let mut buffer = AsyncBuffer::new("GET / HTTP/1.1\r\n\r\n");
+  let result = block_on(async { recognize_response(&mut buffer).await });
+  assert!(result.is_err());
+}
+
+#[test]
+fn recognize_response_bad_version() {
+  let mut buffer = AsyncBuffer::new("HTTP/3 200 OK\r\n\r\n");
+  let result = block_on(async { recognize_response(&mut buffer).await });
+  assert!(result.is_err());
+}
+
+#[test]
+fn recognize_response_repeated_set_cookie() {
+  let mut buffer = AsyncBuffer::new("HTTP/1.1 200 OK\r\nSet-Cookie: a=1\r\nSet-Cookie: b=2\r\n\r\n");
+  let head = block_on(async { recognize_response(&mut buffer).await }).unwrap();
+  let cookies = head.headers().get_all("set-cookie").collect::<Vec<&str>>();
+  assert_eq!(cookies, vec!["a=1", "b=2"]);
+}
+
+#[test]
+fn recognize_response_control_characters() {
+  for line in ["Set-Cookie: a=1\nLocation: /", "Location: /\r", "X: \0"].iter() {
+    let mut buffer = AsyncBuffer::new(format!("HTTP/1.1 200 OK\r\n{}\r\n\r\n", line));
+    let result = block_on(async { recognize_response(&mut buffer).await });
+    assert!(matches!(result, Err(Error::InvalidHeader(_))), "{:?}", result);
+  }
+}
+
+#[test]
+fn recognize_response_folded() {
+  let response = "HTTP/1.1 200 OK\r\nX-Folded: one\r\n\ttwo\r\n\r\n";
+  let mut buffer = AsyncBuffer::new(response);
+  let head = block_on(async { recognize_response(&mut buffer).await }).unwrap();
+  assert_eq!(head.find_header("x-folded"), Some("one two".to_string()));
+
+  let mut buffer = AsyncBuffer::new(response);
+  let result = block_on(async { recognize_response_with(&mut buffer, &RecognizeOptions::default()).await });
+  assert!(matches!(result, Err(Error::InvalidHeader(_))), "{:?}", result);
+}
+
+#[test]
+fn recognize_response_framing() {
+  let cases = [
+    ("HTTP/1.1 200 OK\r\nContent-Length: 3\r\n\r\n", BodyFraming::Length(3)),
+    (
+      "HTTP/1.1 200 OK\r\nContent-Length: 3\r\nTransfer-Encoding: chunked\r\n\r\n",
+      BodyFraming::Chunked,
+    ),
+    ("HTTP/1.1 200 OK\r\n\r\n", BodyFraming::CloseDelimited),
+    (
+      "HTTP/1.1 304 Not Modified\r\nContent-Length: 3\r\n\r\n",
+      BodyFraming::None,
+    ),
+  ];
+
+  for (raw, expected) in cases.iter() {
+    let mut buffer = AsyncBuffer::new(*raw);
+    let head = block_on(async { recognize_response(&mut buffer).await }).unwrap();
+    assert_eq!(head.framing(), *expected, "{:?}", raw);
+  }
+}
@@ -0,0 +1,150 @@
+#![cfg(feature = "server")]
+
+use async_std::future::timeout;
+use async_std::io::{ReadExt, WriteExt};
+use async_std::net::TcpStream;
+use async_std::task::{block_on, spawn, JoinHandle};
+use elaine::server::{Request, Response, Server, Shutdown};
+use std::net::SocketAddr;
+use std::time::Duration;
+
+async fn echo(mut request: Request) -> Response {
+  let path = request.head().path().unwrap_or_default();
+
+  if path == "/skip" {
+    return Response::new(200).unwrap().body("skipped");
+  }
+
+  let mut body = String::new();
+  request.body().read_to_string(&mut body).await.unwrap();
+  Response::new(200).unwrap().body(format!("{} {}", path, body))
+}
+
+fn start(server: Server) -> (SocketAddr, Shutdown, JoinHandle<()>) {
+  let addr = server.local_addr().unwrap();
+  let shutdown = server.shutdown_handle();
+  (addr, shutdown, spawn(server.serve(echo)))
+}
+
+async fn exchange(addr: SocketAddr, requests: &str) -> String {
+  let mut stream = TcpStream::connect(addr).await.unwrap();
+  stream.write_all(requests.as_bytes()).await.unwrap();
+  let mut responses = String::new();
+  stream.read_to_string(&mut responses).await.unwrap();
+  responses
+}
+
+#[test]
+fn server_keep_alive() {
+  block_on(async {
+    let (addr, shutdown, running) = start(Server::bind("127.0.0.1:0").unwrap());
+    let responses = exchange(
+      addr,
+      concat!(
+        "POST /skip HTTP/1.1\r\nContent-Length: 3\r\n\r\nhey",
+        "POST /echo HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nhey\r\n0\r\n\r\n",
+        "HEAD /head HTTP/1.1\r\nConnection: close\r\n\r\n",
+      ),
+    )
+    .await;
+
+    assert_eq!(
+      responses,
+      concat!(
+        "HTTP/1.1 200 OK\r\nContent-Length: 7\r\n\r\nskipped",
+        "HTTP/1.1 200 OK\r\nContent-Length: 9\r\n\r\n/echo hey",
+        "HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Length: 6\r\n\r\n",
+      )
+    );
+
+    shutdown.shutdown();
+    running.await;
+  });
+}
+
+#[test]
+fn server_http10_and_errors() {
+  block_on(async {
+    let (addr, shutdown, running) = start(Server::bind("127.0.0.1:0").unwrap());
+
+    let responses = exchange(addr, "GET /old HTTP/1.0\r\n\r\nGET /ignored HTTP/1.0\r\n\r\n").await;
+    assert_eq!(
+      responses,
+      "HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Length: 5\r\n\r\n/old "
+    );
+
+    let responses = exchange(addr, "GET\r\n\r\n").await;
+    assert_eq!(
+      responses,
+      "HTTP/1.1 400 Bad Request\r\nConnection: close\r\nContent-Length: 0\r\n\r\n"
+    );
+
+    shutdown.shutdown();
+    running.await;
+  });
+}
+
+#[test]
+fn server_timeout() {
+  block_on(async {
+    let server = Server::bind("127.0.0.1:0").unwrap().timeout(Duration::from_millis(50));
+    let (addr, shutdown, running) = start(server);
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let mut rest = Vec::new();
+    let read = timeout(Duration::from_secs(5), stream.read_to_end(&mut rest)).await;
+    assert!(matches!(read, Ok(Ok(0))), "{:?}", read);
+
+    shutdown.shutdown();
+    running.await;
+  });
+}
+
+#[test]
+fn server_concurrency() {
+  block_on(async {
+    let server = Server::bind("127.0.0.1:0").unwrap().concurrency(1);
+    let (addr, shutdown, running) = start(server);
+
+    let mut first = TcpStream::connect(addr).await.unwrap();
+    first.write_all(b"GET /first HTTP/1.1\r\n\r\n").await.unwrap();
+    let mut response = [0u8; 45];
+    first.read_exact(&mut response).await.unwrap();
+    assert!(response.ends_with(b"/first "));
+
+    // The first connection is kept alive, so the second waits to be accepted until it closes.
+    let mut second = TcpStream::connect(addr).await.unwrap();
+    second.write_all(b"GET /second HTTP/1.1\r\n\r\n").await.unwrap();
+    let mut response = [0u8; 46];
+    let waiting = timeout(Duration::from_millis(100), second.read_exact(&mut response)).await;
+    assert!(waiting.is_err());
+
+    drop(first);
+    second.read_exact(&mut response).await.unwrap();
+    assert!(response.ends_with(b"/second "));
+
+    drop(second);
+    shutdown.shutdown();
+    running.await;
+  });
+}
+
+#[test]
+fn server_shutdown() {
+  block_on(async {
+    let (addr, shutdown, running) = start(Server::bind("127.0.0.1:0").unwrap());
+
+    // An idle keep-alive connection does not hold up the shutdown.
+    let mut idle = TcpStream::connect(addr).await.unwrap();
+    idle.write_all(b"GET /idle HTTP/1.1\r\n\r\n").await.unwrap();
+    let mut response = [0u8; 44];
+    idle.read_exact(&mut response).await.unwrap();
+
+    shutdown.shutdown();
+    timeout(Duration::from_secs(5), running).await.unwrap();
+
+    let mut rest = Vec::new();
+    assert_eq!(idle.read_to_end(&mut rest).await.unwrap(), 0);
+    assert!(TcpStream::connect(addr).await.is_err());
+  });
+}
@@ -0,0 +1,100 @@
+#![cfg(feature = "async-std")]
+
+use async_std::io::WriteExt;
+use async_std::net::{TcpListener, TcpStream};
+use async_std::task::{block_on, sleep, spawn};
+use elaine::{
+  recognize_response_with_timeout, recognize_with_timeout, Error, Position, RecognizeOptions, Timeout, Timeouts,
+};
+use std::time::Duration;
+
+async fn pair() -> (TcpStream, TcpStream) {
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let client = TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+  let (server, _) = listener.accept().await.unwrap();
+  (client, server)
+}
+
+fn timeouts(head: u64, idle: u64) -> Timeouts {
+  Timeouts {
+    head: Some(Duration::from_millis(head)),
+    idle: Some(Duration::from_millis(idle)),
+  }
+}
+
+#[test]
+fn timeout_within_limits() {
+  block_on(async {
+    let (mut client, mut server) = pair().await;
+    client
+      .write_all(b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n")
+      .await
+      .unwrap();
+    let head = recognize_with_timeout(&mut server, &RecognizeOptions::default(), &timeouts(1000, 1000))
+      .await
+      .unwrap();
+    assert_eq!(head.find_header("host"), Some("example.com".to_string()));
+  });
+}
+
+#[test]
+fn timeout_idle() {
+  block_on(async {
+    let (mut client, mut server) = pair().await;
+    client.write_all(b"GET / HTTP/1.1\r\nHost: exa").await.unwrap();
+    let result = recognize_with_timeout(&mut server, &RecognizeOptions::default(), &timeouts(5000, 50)).await;
+
+    match result {
+      Err(Error::TimedOut(timed_out)) => {
+        assert_eq!(timed_out.timeout, Timeout::Idle);
+        assert_eq!(timed_out.position, Position { offset: 25, line: 2 });
+        assert_eq!(timed_out.partial, "GET / HTTP/1.1\r\nHost: exa");
+      }
+      other => panic!("unexpected {:?}", other),
+    }
+  });
+}
+
+#[test]
+fn timeout_head_deadline() {
+  block_on(async {
+    let (mut client, mut server) = pair().await;
+
+    // A byte every few milliseconds never trips the idle timeout, only the deadline.
+    let trickle = spawn(async move {
+      for byte in b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n".iter() {
+        if client.write_all(&[*byte]).await.is_err() {
+          break;
+        }
+        sleep(Duration::from_millis(10)).await;
+      }
+    });
+
+    let result = recognize_with_timeout(&mut server, &RecognizeOptions::default(), &timeouts(100, 1000)).await;
+
+    match result {
+      Err(Error::TimedOut(timed_out)) => {
+        assert_eq!(timed_out.timeout, Timeout::Head);
+        assert!(!timed_out.partial.is_empty());
+        assert!(timed_out.partial.len() < 37, "{:?}", timed_out.partial);
+        assert_eq!(timed_out.position.offset, timed_out.partial.len());
+      }
+      other => panic!("unexpected {:?}", other),
+    }
+
+    drop(server);
+    trickle.await;
+  });
+}
+
+#[test]
+fn timeout_response() {
+  block_on(async {
+    let (mut client, mut server) = pair().await;
+    client.write_all(b"HTTP/1.1 200 OK\r\n").await.unwrap();
+    let result = recognize_response_with_timeout(&mut server, &RecognizeOptions::default(), &timeouts(50, 1000)).await;
+    let error = result.unwrap_err();
+    assert!(matches!(error, Error::TimedOut(_)), "{:?}", error);
+    assert_eq!(std::io::Error::from(error).kind(), std::io::ErrorKind::TimedOut);
+  });
+}
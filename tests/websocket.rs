@@ -0,0 +1,276 @@
+#![cfg(test)]
+
+mod helpers;
+
+use async_std::prelude::*;
+use async_std::task::block_on;
+use elaine::websocket::{Close, Frame, Message, Opcode, Reader, Role, Writer};
+use elaine::{recognize, websocket, Error, Head};
+use helpers::AsyncBuffer;
+
+const HANDSHAKE: &str = "GET /chat HTTP/1.1\r\nHost: server.example.com\r\nUpgrade: websocket\r\n\
+                         Connection: keep-alive, Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+                         Sec-WebSocket-Version: 13\r\n";
+
+fn head(extra: &str) -> Head {
+  let mut buffer = AsyncBuffer::new(format!("{}{}\r\n", HANDSHAKE, extra));
+  block_on(async { recognize(&mut buffer).await }).unwrap()
+}
+
+#[test]
+fn websocket_accept() {
+  let head = head("");
+  let mut written = Vec::new();
+  block_on(async { websocket::accept(&head, &mut written).await }).unwrap();
+  assert_eq!(
+    String::from_utf8(written).unwrap(),
+    "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+     Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n\r\n"
+  );
+}
+
+#[test]
+fn websocket_subprotocols() {
+  let head = head("Sec-WebSocket-Protocol: chat, superchat\r\nSec-WebSocket-Protocol: json\r\n");
+  assert_eq!(websocket::protocols(&head), vec!["chat", "superchat", "json"]);
+
+  let (written, selected) =
+    block_on(async { websocket::accept_with(&head, Vec::new(), &["json", "superchat"]).await }).unwrap();
+  assert_eq!(selected, Some("superchat".to_string()));
+  assert!(String::from_utf8(written)
+    .unwrap()
+    .ends_with("Sec-WebSocket-Protocol: superchat\r\n\r\n"));
+
+  let (written, selected) = block_on(async { websocket::accept_with(&head, Vec::new(), &["mqtt"]).await }).unwrap();
+  assert_eq!(selected, None);
+  assert!(!String::from_utf8(written).unwrap().contains("Sec-WebSocket-Protocol"));
+}
+
+#[test]
+fn websocket_rejections() {
+  let reject = |request: &str| {
+    let mut buffer = AsyncBuffer::new(request);
+    let head = block_on(async { recognize(&mut buffer).await }).unwrap();
+    let mut written = Vec::new();
+    let result = block_on(async { websocket::accept(&head, &mut written).await.map(|_| ()) });
+    assert!(written.is_empty());
+    result.unwrap_err()
+  };
+
+  let swap = |from: &str, to: &str| reject(&format!("{}\r\n", HANDSHAKE.replace(from, to)));
+
+  assert!(matches!(swap("GET", "POST"), Error::InvalidMethod(_)));
+  assert!(matches!(swap("HTTP/1.1", "HTTP/1.0"), Error::InvalidVersion(_)));
+  assert!(matches!(
+    swap("Upgrade: websocket", "Upgrade: h2c"),
+    Error::InvalidHeaderValue("Upgrade")
+  ));
+  assert!(matches!(
+    swap("keep-alive, Upgrade", "keep-alive"),
+    Error::InvalidHeaderValue("Connection")
+  ));
+  assert!(matches!(
+    swap("Version: 13", "Version: 8"),
+    Error::InvalidHeaderValue("Sec-WebSocket-Version")
+  ));
+  assert!(matches!(
+    swap("dGhlIHNhbXBsZSBub25jZQ==", "c2hvcnQ="),
+    Error::InvalidHeaderValue("Sec-WebSocket-Key")
+  ));
+  assert!(matches!(
+    swap("Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n", ""),
+    Error::InvalidHeaderValue("Sec-WebSocket-Key")
+  ));
+  assert!(matches!(
+    swap("Host: server.example.com\r\n", ""),
+    Error::InvalidHeaderValue("Host")
+  ));
+  assert!(matches!(
+    reject(&format!("{}Sec-WebSocket-Protocol: a b\r\n\r\n", HANDSHAKE)),
+    Error::InvalidHeaderValue("Sec-WebSocket-Protocol")
+  ));
+}
+
+// Reads what a client wrote to the buffer, as the server.
+fn loopback(messages: &[Message]) -> Vec<Result<Message, Error>> {
+  block_on(async {
+    let mut buffer = AsyncBuffer::new("");
+    let mut writer = Writer::new(&mut buffer, Role::Client).fragment(4);
+    for message in messages {
+      writer.send(message).await.unwrap();
+    }
+
+    let mut reader = Reader::new(&mut buffer, Role::Server);
+    let mut read = Vec::new();
+    for _ in messages {
+      read.push(reader.read().await);
+    }
+    read
+  })
+}
+
+fn read_raw(role: Role, max: usize, bytes: &[u8]) -> Result<Message, Error> {
+  block_on(async {
+    let mut buffer = AsyncBuffer::new("");
+    buffer.write_all(bytes).await.unwrap();
+    Reader::new(&mut buffer, role).max_message(max).read().await
+  })
+}
+
+#[test]
+fn websocket_frames_roundtrip() {
+  let messages = vec![
+    Message::Text("Hello, wörld".to_string()),
+    Message::Binary((0..=255).collect()),
+    Message::Binary(vec![0; 70_000]),
+    Message::Ping(b"are you there".to_vec()),
+    Message::Pong(Vec::new()),
+    Message::Text(String::new()),
+    Message::Close(Some(Close {
+      code: 1000,
+      reason: "done".to_string(),
+    })),
+    Message::Close(None),
+  ];
+
+  let read = loopback(&messages).into_iter().map(Result::unwrap).collect::<Vec<_>>();
+  assert_eq!(read, messages);
+}
+
+#[test]
+fn websocket_frames_wire_format() {
+  let written = block_on(async {
+    let mut writer = Writer::new(Vec::new(), Role::Server).fragment(3);
+    writer.send(&Message::Text("Hello".to_string())).await.unwrap();
+    writer.send(&Message::Binary(vec![7; 300])).await.unwrap();
+    writer.into_inner()
+  });
+  assert_eq!(&written[..9], b"\x01\x03Hel\x80\x02lo");
+  assert_eq!(&written[9..11], b"\x02\x03");
+
+  let written = block_on(async {
+    let mut writer = Writer::new(Vec::new(), Role::Server);
+    writer.send(&Message::Binary(vec![7; 300])).await.unwrap();
+    writer.into_inner()
+  });
+  assert_eq!(&written[..4], b"\x82\x7e\x01\x2c");
+  assert_eq!(written.len(), 304);
+
+  let written = block_on(async {
+    let mut writer = Writer::new(Vec::new(), Role::Client);
+    writer.send(&Message::Text("Hello".to_string())).await.unwrap();
+    writer.into_inner()
+  });
+  assert_eq!(&written[..2], b"\x81\x85");
+  let key = &written[2..6];
+  let payload = written[6..]
+    .iter()
+    .enumerate()
+    .map(|(i, b)| b ^ key[i % 4])
+    .collect::<Vec<_>>();
+  assert_eq!(payload, b"Hello");
+
+  let oversized = block_on(async {
+    let frame = Frame {
+      fin: true,
+      opcode: Opcode::Ping,
+      payload: vec![0; 126],
+    };
+    Writer::new(Vec::new(), Role::Server).write_frame(&frame).await
+  });
+  assert!(matches!(oversized, Err(Error::Io(ref e)) if e.kind() == std::io::ErrorKind::InvalidInput));
+
+  for code in [0, 999, 1004, 1005, 1006, 1015, 2999, 5000] {
+    let written = block_on(async {
+      let mut writer = Writer::new(Vec::new(), Role::Server);
+      let close = Close {
+        code,
+        reason: String::new(),
+      };
+      let result = writer.send(&Message::Close(Some(close))).await;
+      (result, writer.into_inner())
+    });
+    assert!(matches!(written.0, Err(Error::Io(ref e)) if e.kind() == std::io::ErrorKind::InvalidInput));
+    assert!(written.1.is_empty());
+  }
+
+  let truncated = block_on(async {
+    let frame = Frame {
+      fin: true,
+      opcode: Opcode::Close,
+      payload: vec![3],
+    };
+    Writer::new(Vec::new(), Role::Server).write_frame(&frame).await
+  });
+  assert!(matches!(truncated, Err(Error::Io(ref e)) if e.kind() == std::io::ErrorKind::InvalidInput));
+}
+
+#[test]
+fn websocket_frames_fragmented_with_control() {
+  // "Hel", a ping between the fragments, then "lo"; from RFC 6455 §5.6 and §5.7, unmasked.
+  let bytes = b"\x01\x03Hel\x89\x02hi\x80\x02lo";
+  let read = block_on(async {
+    let mut buffer = AsyncBuffer::new("");
+    buffer.write_all(bytes).await.unwrap();
+    let mut reader = Reader::new(&mut buffer, Role::Client);
+    (reader.read().await.unwrap(), reader.read().await.unwrap())
+  });
+  assert_eq!(
+    read,
+    (Message::Ping(b"hi".to_vec()), Message::Text("Hello".to_string()))
+  );
+}
+
+#[test]
+fn websocket_frames_errors() {
+  let code = |result: Result<Message, Error>| match result {
+    Err(Error::InvalidFrame(code)) => code,
+    other => panic!("unexpected {:?}", other),
+  };
+  let client = |bytes: &[u8]| code(read_raw(Role::Client, 16, bytes));
+
+  // Unmasked frames sent to a server, and masked frames sent to a client.
+  assert_eq!(
+    code(read_raw(Role::Server, 16, b"\x81\x02hi")),
+    websocket::PROTOCOL_ERROR
+  );
+  assert_eq!(client(b"\x81\x82\0\0\0\0hi"), websocket::PROTOCOL_ERROR);
+  // Reserved bits, reserved opcodes, fragmented and oversized control frames.
+  assert_eq!(client(b"\xc1\x02hi"), websocket::PROTOCOL_ERROR);
+  assert_eq!(client(b"\x83\x02hi"), websocket::PROTOCOL_ERROR);
+  assert_eq!(client(b"\x09\x02hi"), websocket::PROTOCOL_ERROR);
+  assert_eq!(
+    code(read_raw(
+      Role::Client,
+      1024,
+      &[&b"\x89\x7e\x00\x7e"[..], &[0; 126]].concat()
+    )),
+    websocket::PROTOCOL_ERROR
+  );
+  // Continuations without a message, and messages interrupting one.
+  assert_eq!(client(b"\x80\x02hi"), websocket::PROTOCOL_ERROR);
+  assert_eq!(client(b"\x01\x02hi\x81\x02hi"), websocket::PROTOCOL_ERROR);
+  // Close frames with a lone byte, a reserved code or a reason that is not utf-8.
+  assert_eq!(client(b"\x88\x01\x03"), websocket::PROTOCOL_ERROR);
+  assert_eq!(client(b"\x88\x02\x03\xed"), websocket::PROTOCOL_ERROR);
+  assert_eq!(client(b"\x88\x03\x03\xe8\xff"), websocket::INVALID_PAYLOAD);
+  // Text that is not utf-8, including a sequence split across fragments.
+  assert_eq!(client(b"\x81\x01\xff"), websocket::INVALID_PAYLOAD);
+  assert_eq!(
+    read_raw(Role::Client, 16, b"\x01\x01\xc3\x80\x01\xa9").unwrap(),
+    Message::Text("é".to_string())
+  );
+  // Messages over the maximum size, in one frame or across fragments.
+  assert_eq!(client(b"\x82\x11"), websocket::MESSAGE_TOO_BIG);
+  assert_eq!(
+    client(b"\x82\x7f\xff\xff\xff\xff\xff\xff\xff\xff"),
+    websocket::PROTOCOL_ERROR
+  );
+  assert_eq!(
+    client(b"\x02\x0a0123456789\x80\x0a0123456789"),
+    websocket::MESSAGE_TOO_BIG
+  );
+
+  let truncated = read_raw(Role::Client, 16, b"\x81\x05He");
+  assert!(matches!(truncated, Err(Error::Io(ref e)) if e.kind() == std::io::ErrorKind::UnexpectedEof));
+}